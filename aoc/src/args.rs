@@ -0,0 +1,83 @@
+/* Numbers following a mode flag given first on the command line,
+as in "d5 --gen SEED [RANGES]" or "d5 --diff [RUNS] [RANGES]" */
+pub struct Args {
+    flag: String,
+    n: Vec<u64>,
+}
+
+impl Args {
+    /* None when the program was not started in this mode */
+    pub fn get(flag: &str) -> Option<Args> {
+        Args::from(flag, std::env::args().skip(1))
+    }
+
+    pub fn from<I: IntoIterator<Item = String>>(flag: &str, args: I) -> Option<Args> {
+        let mut args = args.into_iter();
+
+        if args.next().as_deref() != Some(flag) {
+            return None;
+        }
+
+        let n = args
+            .map(|v| {
+                v.parse()
+                    .unwrap_or_else(|_| panic!("{} takes numbers, got {}", flag, v))
+            })
+            .collect();
+
+        Some(Args {
+            flag: flag.to_string(),
+            n,
+        })
+    }
+
+    /* The i-th number, default when the command line stops before it */
+    pub fn num(&self, i: usize, default: u64) -> u64 {
+        self.n.get(i).copied().unwrap_or(default)
+    }
+
+    /* The i-th number which has no sensible default */
+    pub fn need(&self, i: usize, what: &str) -> u64 {
+        *self
+            .n
+            .get(i)
+            .unwrap_or_else(|| panic!("{} needs {}", self.flag, what))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn numbers() {
+        assert!(Args::from("--gen", args(&[])).is_none());
+        assert!(Args::from("--gen", args(&["--diff", "3"])).is_none());
+
+        let a = Args::from("--gen", args(&["--gen", "7", "20"])).unwrap();
+        assert_eq!(a.need(0, "a seed"), 7);
+        assert_eq!(a.num(1, 5), 20);
+        assert_eq!(a.num(2, 5), 5);
+
+        let a = Args::from("--diff", args(&["--diff"])).unwrap();
+        assert_eq!(a.num(0, 100), 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "--gen needs a seed")]
+    fn missing() {
+        Args::from("--gen", args(&["--gen"]))
+            .unwrap()
+            .need(0, "a seed");
+    }
+
+    #[test]
+    #[should_panic(expected = "--diff takes numbers, got x")]
+    fn not_a_number() {
+        Args::from("--diff", args(&["--diff", "x"]));
+    }
+}
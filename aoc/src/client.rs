@@ -0,0 +1,257 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::http::{self, Http, HttpError, Request};
+use crate::ledger::{Ledger, Verdict};
+
+/* Where to talk to and as who, see Config::load */
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub url: String,
+    pub session: Option<String>,
+    pub cache: PathBuf,
+    /* Least time between two requests, even across runs */
+    pub interval: Duration,
+}
+
+fn home() -> PathBuf {
+    std::env::var("HOME").map(PathBuf::from).unwrap_or_default()
+}
+
+impl Config {
+    /* "key = value" lines of $AOC_CONFIG, or ~/.config/aoc/config,
+    with the keys session, url, cache and interval (in seconds).
+    AOC_SESSION, AOC_URL, AOC_CACHE and AOC_INTERVAL win over the file */
+    pub fn load() -> Config {
+        let mut cfg = Config {
+            url: "https://adventofcode.com".to_string(),
+            session: None,
+            cache: home().join(".cache/aoc"),
+            interval: Duration::from_secs(60),
+        };
+
+        let file = std::env::var("AOC_CONFIG")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home().join(".config/aoc/config"));
+
+        let mut set = |key: &str, v: &str| match key {
+            "session" => cfg.session = Some(v.to_string()),
+            "url" => cfg.url = v.trim_end_matches('/').to_string(),
+            "cache" => cfg.cache = PathBuf::from(v),
+            "interval" => {
+                let secs = v
+                    .parse()
+                    .unwrap_or_else(|_| panic!("interval is in seconds, got {}", v));
+                cfg.interval = Duration::from_secs(secs);
+            }
+            _ => eprintln!("Unknown config key {}", key),
+        };
+
+        if let Ok(text) = fs::read_to_string(&file) {
+            for l in text.lines().map(str::trim) {
+                if l.is_empty() || l.starts_with('#') {
+                    continue;
+                }
+                if let Some((k, v)) = l.split_once('=') {
+                    set(k.trim(), v.trim());
+                }
+            }
+        }
+
+        for key in ["session", "url", "cache", "interval"] {
+            if let Ok(v) = std::env::var(format!("AOC_{}", key.to_uppercase())) {
+                set(key, v.trim());
+            }
+        }
+
+        cfg
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    NoSession,
+    Http(HttpError),
+    /* The server answered something else than 200 */
+    Status(u16, String),
+    /* A page that is not a verdict, the text of its message */
+    Refused(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::NoSession => write!(
+                f,
+                "no session token, set AOC_SESSION or session in the config file"
+            ),
+            ClientError::Http(e) => write!(f, "{}", e),
+            ClientError::Status(429, _) => write!(f, "rate limited by the server"),
+            ClientError::Status(s, body) => write!(f, "status {}: {}", s, body.trim()),
+            ClientError::Refused(m) => write!(f, "{}", m),
+            ClientError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<HttpError> for ClientError {
+    fn from(e: HttpError) -> ClientError {
+        ClientError::Http(e)
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(e: io::Error) -> ClientError {
+        ClientError::Io(e)
+    }
+}
+
+/* What became of an answer given to Client::submit_once */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submission {
+    /* The ledger knew it, nothing was sent */
+    Known(Verdict),
+    Sent(Verdict),
+}
+
+/* Text of the <article> holding the message of a page, tags removed */
+fn message(page: &str) -> String {
+    let start = page.find("<article").unwrap_or(0);
+    let end = page[start..]
+        .find("</article>")
+        .map_or(page.len(), |v| start + v);
+
+    let mut ret = String::new();
+    let mut tag = false;
+
+    for c in page[start..end].chars() {
+        match c {
+            '<' => tag = true,
+            '>' => tag = false,
+            _ if !tag => ret.push(c),
+            _ => (),
+        }
+    }
+
+    ret.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub struct Client<H: Http> {
+    http: H,
+    cfg: Config,
+}
+
+impl<H: Http> Client<H> {
+    pub fn new(http: H, cfg: Config) -> Client<H> {
+        Client { http, cfg }
+    }
+
+    pub fn input_path(&self, year: u32, day: u32) -> PathBuf {
+        self.cfg
+            .cache
+            .join(year.to_string())
+            .join(format!("day{}.txt", day))
+    }
+
+    /* Puzzle input, only downloaded when it is not in the cache yet */
+    pub fn input(&self, year: u32, day: u32) -> Result<String, ClientError> {
+        let path = self.input_path(year, day);
+
+        if let Ok(v) = fs::read_to_string(&path) {
+            return Ok(v);
+        }
+
+        let url = format!("{}/{}/day/{}/input", self.cfg.url, year, day);
+        let body = self.send(url, None)?;
+
+        /* Written aside first so a failed write never looks cached */
+        fs::create_dir_all(path.parent().unwrap())?;
+        let tmp = path.with_extension("part");
+        fs::write(&tmp, &body)?;
+        fs::rename(&tmp, &path)?;
+
+        Ok(body)
+    }
+
+    /* Submits answer for a part, the caller keeps the ledger */
+    pub fn submit(
+        &self,
+        year: u32,
+        day: u32,
+        part: u8,
+        answer: &str,
+    ) -> Result<Verdict, ClientError> {
+        let url = format!("{}/{}/day/{}/answer", self.cfg.url, year, day);
+        let form = http::form_encode(&[("level", &part.to_string()), ("answer", answer.trim())]);
+
+        let page = self.send(url, Some(form))?;
+
+        Verdict::from_page(&page).ok_or_else(|| ClientError::Refused(message(&page)))
+    }
+
+    /* Submits answer unless the ledger already tells its verdict,
+    what the site says is recorded in the ledger */
+    pub fn submit_once(
+        &self,
+        ledger: &mut Ledger,
+        year: u32,
+        day: u32,
+        part: u8,
+        answer: &str,
+    ) -> Result<Submission, ClientError> {
+        if let Some(v) = ledger.judge(part, answer) {
+            return Ok(Submission::Known(v));
+        }
+
+        let verdict = self.submit(year, day, part, answer)?;
+        ledger.record(part, answer, verdict)?;
+
+        Ok(Submission::Sent(verdict))
+    }
+
+    fn send(&self, url: String, form: Option<String>) -> Result<String, ClientError> {
+        let session = self.cfg.session.clone().ok_or(ClientError::NoSession)?;
+
+        self.throttle()?;
+
+        let res = self.http.send(&Request { url, session, form })?;
+
+        if res.status != 200 {
+            return Err(ClientError::Status(res.status, res.body));
+        }
+
+        Ok(res.body)
+    }
+
+    /* Waits until interval went by since the last request, which
+    is stamped in the cache so separate runs share the limit */
+    fn throttle(&self) -> Result<(), ClientError> {
+        let stamp = self.cfg.cache.join("last_request");
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+        let last = fs::read_to_string(&stamp)
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs);
+
+        let mut at = now;
+
+        if let Some(wait) = last.and_then(|l| (l + self.cfg.interval).checked_sub(now)) {
+            eprintln!("Waiting {}s between requests", wait.as_secs() + 1);
+            thread::sleep(wait);
+            at += wait;
+        }
+
+        fs::create_dir_all(&self.cfg.cache)?;
+        fs::write(&stamp, at.as_secs().to_string())?;
+
+        Ok(())
+    }
+}
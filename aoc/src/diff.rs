@@ -0,0 +1,236 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::rng::Rng;
+
+/* Two solvers of the same puzzle, a fast one and a slow reference
+which is obviously right, checked against each other on random inputs */
+pub trait Differential {
+    type Input: Clone + fmt::Display;
+    type Output: PartialEq + fmt::Debug;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Input;
+
+    /* Smaller variants of a valid input, each one valid as well */
+    fn shrink(&self, input: &Self::Input) -> Vec<Self::Input>;
+
+    fn fast(&self, input: &Self::Input) -> Self::Output;
+
+    fn reference(&self, input: &Self::Input) -> Self::Output;
+}
+
+/* What a solver gave, a panic counts as an answer of its own */
+pub type Outcome<O> = Result<O, String>;
+
+type Outcomes<O> = (Outcome<O>, Outcome<O>);
+
+#[derive(Debug)]
+pub struct Failure<I, O> {
+    pub seed: u64,
+    pub shrinks: usize,
+    pub input: I,
+    pub fast: Outcome<O>,
+    pub reference: Outcome<O>,
+}
+
+impl<I: fmt::Display, O: fmt::Debug> fmt::Display for Failure<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Seed {} disagrees, smallest input after {} shrinks:",
+            self.seed, self.shrinks
+        )?;
+        writeln!(f, "{}", self.input)?;
+        writeln!(f, "fast      : {:?}", self.fast)?;
+        write!(f, "reference : {:?}", self.reference)
+    }
+}
+
+fn outcome<O>(f: impl FnOnce() -> O) -> Outcome<O> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|e| {
+        e.downcast_ref::<&str>()
+            .map(|v| v.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string())
+    })
+}
+
+/* Both outcomes when the solvers disagree on input */
+fn disagree<D: Differential>(d: &D, input: &D::Input) -> Option<Outcomes<D::Output>> {
+    let fast = outcome(|| d.fast(input));
+    let reference = outcome(|| d.reference(input));
+
+    (fast != reference).then_some((fast, reference))
+}
+
+/* Runs the seeds in order and stops at the first disagreement, which
+is shrunk as long as one of its smaller variants still disagrees */
+pub fn check<D: Differential>(
+    d: &D,
+    seeds: impl IntoIterator<Item = u64>,
+) -> Result<usize, Failure<D::Input, D::Output>> {
+    /* Panics are part of the outcome, not something to print, the
+    hook can't be set while unwinding so panics of generate or
+    shrink are caught and raised again once it is back */
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let found = outcome(|| first_failure(d, seeds));
+
+    panic::set_hook(hook);
+
+    match found {
+        Ok(v) => v,
+        Err(e) => panic!("Generating or shrinking inputs failed: {}", e),
+    }
+}
+
+fn first_failure<D: Differential>(
+    d: &D,
+    seeds: impl IntoIterator<Item = u64>,
+) -> Result<usize, Failure<D::Input, D::Output>> {
+    let mut passed = 0;
+
+    for seed in seeds {
+        let input = d.generate(&mut Rng::new(seed));
+
+        let (fast, reference) = match disagree(d, &input) {
+            Some(v) => v,
+            None => {
+                passed += 1;
+                continue;
+            }
+        };
+
+        let mut failure = Failure {
+            seed,
+            shrinks: 0,
+            input,
+            fast,
+            reference,
+        };
+
+        'shrink: loop {
+            for smaller in d.shrink(&failure.input) {
+                if let Some((fast, reference)) = disagree(d, &smaller) {
+                    failure.input = smaller;
+                    failure.fast = fast;
+                    failure.reference = reference;
+                    failure.shrinks += 1;
+                    continue 'shrink;
+                }
+            }
+            break;
+        }
+
+        return Err(failure);
+    }
+
+    Ok(passed)
+}
+
+/* Prints the outcome of check, the process fails on a disagreement */
+pub fn run<D: Differential>(d: &D, runs: u64) {
+    match check(d, 0..runs) {
+        Ok(passed) => println!("{} inputs, no disagreement", passed),
+        Err(f) => {
+            println!("{}", f);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /* The panic hook is global, tests swapping it take turns */
+    static HOOK: Mutex<()> = Mutex::new(());
+
+    /* Sums a list, the fast sum is off by one once a value reaches 50 */
+    struct Sum {
+        broken_gen: bool,
+    }
+
+    impl Differential for Sum {
+        type Input = Numbers;
+        type Output = u64;
+
+        fn generate(&self, rng: &mut Rng) -> Numbers {
+            assert!(!self.broken_gen, "no inputs today");
+            Numbers((0..1 + rng.below(20)).map(|_| rng.below(100)).collect())
+        }
+
+        /* Drop a value, halve one or take one off it */
+        fn shrink(&self, input: &Numbers) -> Vec<Numbers> {
+            let mut ret = Vec::new();
+
+            for x in 0..input.0.len() {
+                let mut v = input.0.clone();
+                v.remove(x);
+                ret.push(Numbers(v));
+
+                for smaller in [input.0[x] / 2, input.0[x].saturating_sub(1)] {
+                    let mut v = input.0.clone();
+                    v[x] = smaller;
+                    ret.push(Numbers(v));
+                }
+            }
+
+            ret
+        }
+
+        fn fast(&self, input: &Numbers) -> u64 {
+            input
+                .0
+                .iter()
+                .map(|v| if *v >= 50 { v + 1 } else { *v })
+                .sum()
+        }
+
+        fn reference(&self, input: &Numbers) -> u64 {
+            input.0.iter().sum()
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Numbers(Vec<u64>);
+
+    impl fmt::Display for Numbers {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    #[test]
+    fn wrong_solver_shrinks() {
+        let _turn = HOOK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let f = check(&Sum { broken_gen: false }, 0..100).unwrap_err();
+
+        assert_eq!(f.input, Numbers(vec![50]));
+        assert_eq!(f.fast, Ok(51));
+        assert_eq!(f.reference, Ok(50));
+        assert!(f.shrinks > 0);
+    }
+
+    #[test]
+    fn hook_restored() {
+        let _turn = HOOK.lock().unwrap_or_else(|e| e.into_inner());
+
+        static SEEN: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        panic::set_hook(Box::new(|i| {
+            SEEN.lock().unwrap().push(i.to_string());
+        }));
+
+        let ret = panic::catch_unwind(|| check(&Sum { broken_gen: true }, 0..10));
+
+        /* The panic of generate is raised again with the hook back */
+        let _ = panic::take_hook();
+        assert!(ret.is_err());
+        let seen = SEEN.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(seen[0].contains("no inputs today"), "{}", seen[0]);
+    }
+}
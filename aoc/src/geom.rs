@@ -0,0 +1,270 @@
+use std::fmt;
+use std::ops::{Add, Sub};
+
+/* Compass directions on a grid, rows grow going South and columns
+going East. Cardinals come first so their index fits in 0..4 */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    N,
+    E,
+    S,
+    W,
+    NE,
+    SE,
+    SW,
+    NW,
+}
+
+impl Dir {
+    pub const CARDINALS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
+
+    /* Clockwise from North */
+    pub const ALL: [Dir; 8] = [
+        Dir::N,
+        Dir::NE,
+        Dir::E,
+        Dir::SE,
+        Dir::S,
+        Dir::SW,
+        Dir::W,
+        Dir::NW,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.index() >= 4
+    }
+
+    /* (rows, cols) moved by one step */
+    pub fn delta(self) -> (i64, i64) {
+        match self {
+            Dir::N => (-1, 0),
+            Dir::NE => (-1, 1),
+            Dir::E => (0, 1),
+            Dir::SE => (1, 1),
+            Dir::S => (1, 0),
+            Dir::SW => (1, -1),
+            Dir::W => (0, -1),
+            Dir::NW => (-1, -1),
+        }
+    }
+
+    pub fn from_delta(d: (i64, i64)) -> Option<Dir> {
+        Dir::ALL.iter().find(|v| v.delta() == d).copied()
+    }
+
+    /* Turn by steps of 45°, clockwise when positive */
+    pub fn rotate(self, eighths: i32) -> Dir {
+        let cur = Dir::ALL.iter().position(|v| *v == self).unwrap() as i32;
+        Dir::ALL[(cur + eighths).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right(self) -> Dir {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Dir {
+        self.rotate(-2)
+    }
+
+    pub fn reverse(self) -> Dir {
+        self.rotate(4)
+    }
+
+    /* Bounced on a '/' mirror, N and E are swapped as are S and W */
+    pub fn reflect_slash(self) -> Dir {
+        let (r, c) = self.delta();
+        Dir::from_delta((-c, -r)).unwrap()
+    }
+
+    /* Bounced on a '\' mirror, N and W are swapped as are S and E */
+    pub fn reflect_backslash(self) -> Dir {
+        let (r, c) = self.delta();
+        Dir::from_delta((c, r)).unwrap()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dir::N => "N",
+            Dir::NE => "NE",
+            Dir::E => "E",
+            Dir::SE => "SE",
+            Dir::S => "S",
+            Dir::SW => "SW",
+            Dir::W => "W",
+            Dir::NW => "NW",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Dir> {
+        Dir::ALL.iter().find(|v| v.name() == s).copied()
+    }
+}
+
+impl fmt::Display for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/* Signed grid position, it may lie outside of any grid */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Pos {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl Pos {
+    pub fn new(row: i64, col: i64) -> Pos {
+        Pos { row, col }
+    }
+
+    pub fn step(self, d: Dir) -> Pos {
+        self.step_n(d, 1)
+    }
+
+    pub fn step_n(self, d: Dir, n: i64) -> Pos {
+        let (r, c) = d.delta();
+        Pos::new(self.row + r * n, self.col + c * n)
+    }
+
+    pub fn neighbours4(self) -> impl Iterator<Item = Pos> {
+        Dir::CARDINALS.into_iter().map(move |d| self.step(d))
+    }
+
+    pub fn neighbours8(self) -> impl Iterator<Item = Pos> {
+        Dir::ALL.into_iter().map(move |d| self.step(d))
+    }
+
+    pub fn manhattan(self, o: Pos) -> i64 {
+        (self.row - o.row).abs() + (self.col - o.col).abs()
+    }
+
+    /* (row, col) usable as a grid index, None when negative */
+    pub fn cell(self) -> Option<(usize, usize)> {
+        if self.row < 0 || self.col < 0 {
+            return None;
+        }
+        Some((self.row as usize, self.col as usize))
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from(v: (usize, usize)) -> Pos {
+        Pos::new(v.0 as i64, v.1 as i64)
+    }
+}
+
+impl Add for Pos {
+    type Output = Pos;
+
+    fn add(self, o: Pos) -> Pos {
+        Pos::new(self.row + o.row, self.col + o.col)
+    }
+}
+
+impl Sub for Pos {
+    type Output = Pos;
+
+    fn sub(self, o: Pos) -> Pos {
+        Pos::new(self.row - o.row, self.col - o.col)
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.row, self.col)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* dir, delta, right, left, reverse, on '/', on '\' */
+    type Row = (Dir, (i64, i64), Dir, Dir, Dir, Dir, Dir);
+
+    #[rustfmt::skip]
+    const TABLE: [Row; 8] = [
+        (Dir::N,  (-1, 0),  Dir::E,  Dir::W,  Dir::S,  Dir::E,  Dir::W),
+        (Dir::NE, (-1, 1),  Dir::SE, Dir::NW, Dir::SW, Dir::NE, Dir::SW),
+        (Dir::E,  (0, 1),   Dir::S,  Dir::N,  Dir::W,  Dir::N,  Dir::S),
+        (Dir::SE, (1, 1),   Dir::SW, Dir::NE, Dir::NW, Dir::NW, Dir::SE),
+        (Dir::S,  (1, 0),   Dir::W,  Dir::E,  Dir::N,  Dir::W,  Dir::E),
+        (Dir::SW, (1, -1),  Dir::NW, Dir::SE, Dir::NE, Dir::SW, Dir::NE),
+        (Dir::W,  (0, -1),  Dir::N,  Dir::S,  Dir::E,  Dir::S,  Dir::N),
+        (Dir::NW, (-1, -1), Dir::NE, Dir::SW, Dir::SE, Dir::SE, Dir::NW),
+    ];
+
+    #[test]
+    fn directions() {
+        for (d, delta, right, left, reverse, slash, backslash) in TABLE {
+            assert_eq!(d.delta(), delta, "{}", d);
+            assert_eq!(Dir::from_delta(delta), Some(d), "{}", d);
+            assert_eq!(d.turn_right(), right, "{}", d);
+            assert_eq!(d.turn_left(), left, "{}", d);
+            assert_eq!(d.reverse(), reverse, "{}", d);
+            assert_eq!(d.reflect_slash(), slash, "{}", d);
+            assert_eq!(d.reflect_backslash(), backslash, "{}", d);
+            assert_eq!(Dir::parse(d.name()), Some(d));
+            assert_eq!(d.is_diagonal(), delta.0 != 0 && delta.1 != 0);
+
+            /* Mirrors send a beam back the way it came when hit twice */
+            assert_eq!(slash.reflect_slash(), d);
+            assert_eq!(backslash.reflect_backslash(), d);
+
+            assert_eq!(Pos::new(5, -2).step(d), Pos::new(5 + delta.0, -2 + delta.1));
+            assert_eq!(
+                Pos::new(0, 0).step_n(d, 3),
+                Pos::new(3 * delta.0, 3 * delta.1)
+            );
+        }
+
+        assert_eq!(Dir::from_delta((0, 0)), None);
+        assert_eq!(Dir::from_delta((2, 0)), None);
+        assert_eq!(Dir::parse("n"), None);
+    }
+
+    #[test]
+    fn rotations() {
+        assert_eq!(Dir::N.rotate(1), Dir::NE);
+        assert_eq!(Dir::N.rotate(-1), Dir::NW);
+        assert_eq!(Dir::W.rotate(10), Dir::N);
+        assert_eq!(Dir::E.rotate(-16), Dir::E);
+
+        for d in Dir::CARDINALS {
+            assert_eq!(
+                d.index(),
+                Dir::CARDINALS.iter().position(|v| *v == d).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn positions() {
+        let p = Pos::new(2, 3);
+
+        assert_eq!(p + Pos::new(-4, 1) - Pos::new(1, 1), Pos::new(-3, 3));
+        assert_eq!(p.manhattan(Pos::new(-1, 7)), 7);
+        assert_eq!(p.cell(), Some((2, 3)));
+        assert_eq!(Pos::new(-1, 3).cell(), None);
+        assert_eq!(Pos::new(2, -1).cell(), None);
+        assert_eq!(Pos::from((4, 5)), Pos::new(4, 5));
+        assert_eq!(p.to_string(), "2:3");
+
+        let n4: Vec<Pos> = Pos::new(0, 0).neighbours4().collect();
+        assert_eq!(
+            n4,
+            [
+                Pos::new(-1, 0),
+                Pos::new(0, 1),
+                Pos::new(1, 0),
+                Pos::new(0, -1)
+            ]
+        );
+        assert_eq!(Pos::new(0, 0).neighbours8().count(), 8);
+    }
+}
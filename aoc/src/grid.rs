@@ -0,0 +1,536 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::geom::{Dir, Pos};
+
+/* Rectangular grid stored row major, every position
+is a (row, col) pair with (0, 0) the top left corner */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /* A row does not have the width of the first one */
+    Ragged {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+    /* Flat data does not fill rows x cols */
+    Size {
+        len: usize,
+        rows: usize,
+        cols: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::Ragged { row, len, expected } => write!(
+                f,
+                "row {} has {} columns where {} were expected",
+                row, len, expected
+            ),
+            GridError::Size { len, rows, cols } => {
+                write!(f, "{} values cannot fill {}x{}", len, rows, cols)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, cols: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            data: vec![fill; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    pub fn from_vec(data: Vec<T>, rows: usize, cols: usize) -> Result<Grid<T>, GridError> {
+        if data.len() != rows * cols {
+            return Err(GridError::Size {
+                len: data.len(),
+                rows,
+                cols,
+            });
+        }
+
+        Ok(Grid { data, rows, cols })
+    }
+
+    pub fn from_rows<I: IntoIterator<Item = Vec<T>>>(rows: I) -> Result<Grid<T>, GridError> {
+        let mut ret = Grid {
+            data: Vec::new(),
+            rows: 0,
+            cols: 0,
+        };
+
+        for r in rows {
+            ret.push_row(r)?;
+        }
+
+        Ok(ret)
+    }
+
+    /* The first row sets the width of the grid */
+    pub fn push_row(&mut self, row: Vec<T>) -> Result<(), GridError> {
+        if self.rows == 0 {
+            self.cols = row.len();
+        } else if row.len() != self.cols {
+            return Err(GridError::Ragged {
+                row: self.rows,
+                len: row.len(),
+                expected: self.cols,
+            });
+        }
+
+        self.data.extend(row);
+        self.rows += 1;
+
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn contains(&self, pos: (usize, usize)) -> bool {
+        pos.0 < self.rows && pos.1 < self.cols
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.data[pos.0 * self.cols + pos.1])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.data[pos.0 * self.cols + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /* Returns the previous value, None (and nothing set) when out of the grid */
+    pub fn set(&mut self, pos: (usize, usize), v: T) -> Option<T> {
+        self.get_mut(pos).map(|old| std::mem::replace(old, v))
+    }
+
+    /* pos moved by a signed (rows, cols) offset if it stays in the grid */
+    pub fn offset(&self, pos: (usize, usize), d: (i64, i64)) -> Option<(usize, usize)> {
+        let r = pos.0 as i64 + d.0;
+        let c = pos.1 as i64 + d.1;
+
+        if r < 0 || c < 0 {
+            return None;
+        }
+
+        let ret = (r as usize, c as usize);
+        self.contains(ret).then_some(ret)
+    }
+
+    /* One step from pos if it stays in the grid */
+    pub fn step(&self, pos: (usize, usize), d: Dir) -> Option<(usize, usize)> {
+        self.offset(pos, d.delta())
+    }
+
+    /* Cell at a signed position if it is in the grid */
+    pub fn locate(&self, p: Pos) -> Option<(usize, usize)> {
+        p.cell().filter(|v| self.contains(*v))
+    }
+
+    /* Up, right, down and left neighbours inside the grid */
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        Dir::CARDINALS
+            .into_iter()
+            .filter_map(move |d| self.step(pos, d))
+    }
+
+    /* Same with the diagonals, clockwise from up */
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        Dir::ALL.into_iter().filter_map(move |d| self.step(pos, d))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let cols = self.cols;
+        (0..self.rows * cols).map(move |i| (i / cols, i % cols))
+    }
+
+    /* Values in row major order */
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.data.iter_mut()
+    }
+
+    /* Values with their position in row major order */
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.data.iter())
+    }
+
+    pub fn find(&self, v: &T) -> Option<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        self.cells().find(|(_, x)| *x == v).map(|(p, _)| p)
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        &self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> &mut [T] {
+        &mut self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn rows_iter(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.rows).map(move |r| self.row(r))
+    }
+
+    pub fn col(&self, c: usize) -> impl Iterator<Item = &T> {
+        self.data
+            .iter()
+            .skip(c)
+            .step_by(self.cols.max(1))
+            .take(self.rows)
+    }
+
+    pub fn cols_iter(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.cols).map(move |c| self.col(c))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    pub fn view(&self) -> View<'_, T> {
+        View {
+            g: self,
+            swap: false,
+            flip_r: false,
+            flip_c: false,
+        }
+    }
+
+    pub fn transpose(&self) -> View<'_, T> {
+        self.view().transpose()
+    }
+
+    pub fn rotate_cw(&self) -> View<'_, T> {
+        self.view().rotate_cw()
+    }
+
+    pub fn rotate_ccw(&self) -> View<'_, T> {
+        self.view().rotate_ccw()
+    }
+
+    pub fn flip_rows(&self) -> View<'_, T> {
+        self.view().flip_rows()
+    }
+
+    pub fn flip_cols(&self) -> View<'_, T> {
+        self.view().flip_cols()
+    }
+}
+
+impl Grid<u8> {
+    /* One row per line, all lines must have the same length */
+    pub fn parse(text: &str) -> Result<Grid<u8>, GridError> {
+        Grid::from_rows(
+            text.trim_end_matches(['\n', '\r'])
+                .lines()
+                .map(|l| l.as_bytes().to_vec()),
+        )
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        assert!(self.contains(pos), "{:?} is out of the grid", pos);
+        &self.data[pos.0 * self.cols + pos.1]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        assert!(self.contains(pos), "{:?} is out of the grid", pos);
+        &mut self.data[pos.0 * self.cols + pos.1]
+    }
+}
+
+impl fmt::Display for Grid<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in self.rows_iter() {
+            writeln!(f, "{}", String::from_utf8_lossy(r))?;
+        }
+        Ok(())
+    }
+}
+
+/* Transposed, rotated or flipped grid without copying it. A view
+position is mapped back by reversing its row and/or col then
+swapping both when the view is transposed */
+#[derive(Debug, Clone, Copy)]
+pub struct View<'a, T> {
+    g: &'a Grid<T>,
+    swap: bool,
+    flip_r: bool,
+    flip_c: bool,
+}
+
+impl<'a, T> View<'a, T> {
+    pub fn rows(&self) -> usize {
+        if self.swap {
+            self.g.cols
+        } else {
+            self.g.rows
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        if self.swap {
+            self.g.rows
+        } else {
+            self.g.cols
+        }
+    }
+
+    fn source(&self, pos: (usize, usize)) -> Option<(usize, usize)> {
+        if pos.0 >= self.rows() || pos.1 >= self.cols() {
+            return None;
+        }
+
+        let r = if self.flip_r {
+            self.rows() - 1 - pos.0
+        } else {
+            pos.0
+        };
+
+        let c = if self.flip_c {
+            self.cols() - 1 - pos.1
+        } else {
+            pos.1
+        };
+
+        Some(if self.swap { (c, r) } else { (r, c) })
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&'a T> {
+        self.source(pos).and_then(|p| self.g.get(p))
+    }
+
+    pub fn row(&self, r: usize) -> impl Iterator<Item = &'a T> + '_ {
+        (0..self.cols()).filter_map(move |c| self.get((r, c)))
+    }
+
+    pub fn rows_iter(&self) -> impl Iterator<Item = impl Iterator<Item = &'a T> + '_> + '_ {
+        (0..self.rows()).map(move |r| self.row(r))
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut data = Vec::with_capacity(self.rows() * self.cols());
+
+        for r in 0..self.rows() {
+            data.extend(self.row(r).cloned());
+        }
+
+        Grid {
+            data,
+            rows: self.rows(),
+            cols: self.cols(),
+        }
+    }
+
+    /* Apply (swap, flip_r, flip_c) on top of this view */
+    fn then(&self, swap: bool, flip_r: bool, flip_c: bool) -> View<'a, T> {
+        let (r_src, c_src) = if swap {
+            (self.flip_c, self.flip_r)
+        } else {
+            (self.flip_r, self.flip_c)
+        };
+
+        View {
+            g: self.g,
+            swap: self.swap ^ swap,
+            flip_r: flip_r ^ r_src,
+            flip_c: flip_c ^ c_src,
+        }
+    }
+
+    pub fn transpose(&self) -> View<'a, T> {
+        self.then(true, false, false)
+    }
+
+    pub fn rotate_cw(&self) -> View<'a, T> {
+        self.then(true, false, true)
+    }
+
+    pub fn rotate_ccw(&self) -> View<'a, T> {
+        self.then(true, true, false)
+    }
+
+    /* Upside down */
+    pub fn flip_rows(&self) -> View<'a, T> {
+        self.then(false, true, false)
+    }
+
+    /* Left to right mirror */
+    pub fn flip_cols(&self) -> View<'a, T> {
+        self.then(false, false, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn text(v: View<u8>) -> String {
+        v.to_grid().to_string()
+    }
+
+    #[test]
+    fn views() {
+        let g = Grid::parse("abc\ndef\n").unwrap();
+
+        assert_eq!(text(g.view()), "abc\ndef\n");
+        assert_eq!(text(g.transpose()), "ad\nbe\ncf\n");
+        assert_eq!(text(g.rotate_cw()), "da\neb\nfc\n");
+        assert_eq!(text(g.rotate_ccw()), "cf\nbe\nad\n");
+        assert_eq!(text(g.flip_rows()), "def\nabc\n");
+        assert_eq!(text(g.flip_cols()), "cba\nfed\n");
+
+        let v = g.rotate_cw();
+        assert_eq!((v.rows(), v.cols()), (3, 2));
+        assert_eq!(v.get((0, 1)), Some(&b'a'));
+        assert_eq!(v.get((2, 0)), Some(&b'f'));
+        assert_eq!(v.get((0, 2)), None);
+        assert_eq!(v.get((3, 0)), None);
+    }
+
+    #[test]
+    fn identities() {
+        let g = Grid::parse("abcd\nefgh\nijkl\n").unwrap();
+
+        let v = g.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+        assert_eq!(v.to_grid(), g);
+        assert_eq!(g.transpose().transpose().to_grid(), g);
+        assert_eq!(g.rotate_cw().rotate_ccw().to_grid(), g);
+        assert_eq!(g.flip_rows().flip_rows().to_grid(), g);
+        assert_eq!(
+            g.rotate_cw().rotate_cw().to_grid(),
+            g.flip_rows().flip_cols().to_grid()
+        );
+    }
+
+    /* Copying reference of each view, the op numbers of chains_match_copies */
+    fn copy(g: &Grid<u8>, op: usize) -> Grid<u8> {
+        let (rows, cols) = (g.rows(), g.cols());
+
+        let mut ret = if op < 3 {
+            Grid::new(cols, rows, b'.')
+        } else {
+            Grid::new(rows, cols, b'.')
+        };
+
+        for (r, c) in ret.positions() {
+            ret[(r, c)] = g[match op {
+                0 => (c, r),
+                1 => (rows - 1 - c, r),
+                2 => (c, cols - 1 - r),
+                3 => (rows - 1 - r, c),
+                _ => (r, cols - 1 - c),
+            }];
+        }
+
+        ret
+    }
+
+    #[test]
+    fn chains_match_copies() {
+        for seed in 0..200 {
+            let mut rng = Rng::new(seed);
+            let (rows, cols) = (1 + rng.index(5), 1 + rng.index(5));
+
+            let data = (0..rows * cols)
+                .map(|_| b'a' + rng.below(26) as u8)
+                .collect();
+            let g = Grid::from_vec(data, rows, cols).unwrap();
+
+            let mut v = g.view();
+            let mut want = g.clone();
+
+            for _ in 0..rng.index(6) {
+                let op = rng.index(5);
+
+                v = match op {
+                    0 => v.transpose(),
+                    1 => v.rotate_cw(),
+                    2 => v.rotate_ccw(),
+                    3 => v.flip_rows(),
+                    _ => v.flip_cols(),
+                };
+                want = copy(&want, op);
+
+                assert_eq!(v.to_grid(), want, "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let g = Grid::parse("ab\r\ncd\r\n\n").unwrap();
+        assert_eq!((g.rows(), g.cols()), (2, 2));
+        assert_eq!(g[(1, 0)], b'c');
+
+        assert_eq!(
+            Grid::parse("ab\nc\nde\n"),
+            Err(GridError::Ragged {
+                row: 1,
+                len: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Grid::parse("ab\ncd\ndef").unwrap_err().to_string(),
+            "row 2 has 3 columns where 2 were expected"
+        );
+        assert_eq!(
+            Grid::from_vec(vec![1, 2, 3], 2, 2),
+            Err(GridError::Size {
+                len: 3,
+                rows: 2,
+                cols: 2
+            })
+        );
+    }
+}
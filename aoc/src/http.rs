@@ -0,0 +1,281 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Command, Stdio};
+
+/* GET, or POST of an url encoded form when there is one */
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub url: String,
+    pub session: String,
+    pub form: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub enum HttpError {
+    Io(io::Error),
+    /* The backend cannot handle this url */
+    Url(String),
+    /* Something that does not read as an HTTP answer */
+    Malformed(String),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HttpError::Io(e) => write!(f, "{}", e),
+            HttpError::Url(u) => write!(f, "cannot request {}", u),
+            HttpError::Malformed(m) => write!(f, "bad answer: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<io::Error> for HttpError {
+    fn from(e: io::Error) -> HttpError {
+        HttpError::Io(e)
+    }
+}
+
+/* Whatever carries the requests, a non 2xx status is
+still a response, the caller decides what it means */
+pub trait Http {
+    fn send(&self, req: &Request) -> Result<Response, HttpError>;
+}
+
+/* The site asks automated clients to say who they are */
+pub fn user_agent() -> String {
+    std::env::var("AOC_USER_AGENT").unwrap_or_else(|_| "aoc-rust-2023 input cache".to_string())
+}
+
+/* Shells out to curl, which does TLS for us. The session goes
+through stdin as "-H @-" so it never shows in the process list */
+pub struct Curl;
+
+impl Http for Curl {
+    fn send(&self, req: &Request) -> Result<Response, HttpError> {
+        let mut cmd = Command::new("curl");
+
+        cmd.args(["-sS", "-A", &user_agent()])
+            .args(["-H", "@-"])
+            .args(["-w", "\n%{http_code}"]);
+
+        if let Some(form) = &req.form {
+            cmd.args(["--data", form]);
+        }
+
+        let mut child = cmd
+            .arg(&req.url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        /* Dropped right after so curl sees the end of the headers */
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(format!("Cookie: session={}\n", req.session).as_bytes())?;
+
+        let out = child.wait_with_output()?;
+
+        if !out.status.success() {
+            return Err(HttpError::Malformed(
+                String::from_utf8_lossy(&out.stderr).trim().to_string(),
+            ));
+        }
+
+        /* Status code comes last on a line of its own */
+        let out = String::from_utf8_lossy(&out.stdout);
+        let (body, status) = out
+            .rsplit_once('\n')
+            .ok_or_else(|| HttpError::Malformed("no status code".to_string()))?;
+
+        Ok(Response {
+            status: status
+                .parse()
+                .map_err(|_| HttpError::Malformed(format!("status {}", status)))?,
+            body: body.to_string(),
+        })
+    }
+}
+
+/* host:port of an url's host, port 80 unless it has one.
+"localhost" alone is not an address yet, nor is "[::1]" */
+fn address(host: &str) -> String {
+    match host.rsplit_once(':') {
+        Some((_, port)) if !port.ends_with(']') => host.to_string(),
+        _ => format!("{}:80", host),
+    }
+}
+
+/* Bare HTTP/1.0 over TCP, enough for a local server */
+pub struct Plain;
+
+impl Http for Plain {
+    fn send(&self, req: &Request) -> Result<Response, HttpError> {
+        let rest = req
+            .url
+            .strip_prefix("http://")
+            .ok_or_else(|| HttpError::Url(req.url.clone()))?;
+        let (host, path) = match rest.find('/') {
+            Some(x) => (&rest[..x], &rest[x..]),
+            None => (rest, "/"),
+        };
+
+        let mut s = TcpStream::connect(address(host))?;
+
+        let method = if req.form.is_some() { "POST" } else { "GET" };
+        let mut head = format!(
+            "{} {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: {}\r\nCookie: session={}\r\n",
+            method,
+            path,
+            host,
+            user_agent(),
+            req.session
+        );
+
+        if let Some(form) = &req.form {
+            head.push_str("Content-Type: application/x-www-form-urlencoded\r\n");
+            head.push_str(&format!("Content-Length: {}\r\n", form.len()));
+        }
+        head.push_str("\r\n");
+        head.push_str(req.form.as_deref().unwrap_or(""));
+
+        s.write_all(head.as_bytes())?;
+
+        let mut r = BufReader::new(s);
+        let mut line = String::new();
+        r.read_line(&mut line)?;
+
+        /* "HTTP/1.0 200 OK" */
+        let status = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| HttpError::Malformed(line.trim().to_string()))?;
+
+        loop {
+            line.clear();
+            if r.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let mut body = String::new();
+        r.read_to_string(&mut body)?;
+
+        Ok(Response { status, body })
+    }
+}
+
+/* "k=v&k=v" body, anything but letters and digits is %XX escaped */
+pub fn form_encode(pairs: &[(&str, &str)]) -> String {
+    let enc = |s: &str| -> String {
+        s.bytes()
+            .map(|b| {
+                if b.is_ascii_alphanumeric() {
+                    (b as char).to_string()
+                } else {
+                    format!("%{:02X}", b)
+                }
+            })
+            .collect()
+    };
+
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", enc(k), enc(v)))
+        .collect();
+    pairs.join("&")
+}
+
+pub fn form_decode(body: &str) -> Vec<(String, String)> {
+    let dec = |s: &str| -> String {
+        let b = s.as_bytes();
+        let mut ret = Vec::new();
+        let mut x = 0;
+
+        while x < b.len() {
+            let hex = b
+                .get(x + 1..x + 3)
+                .and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+
+            match (b[x], hex) {
+                (b'%', Some(v)) => {
+                    ret.push(v);
+                    x += 3;
+                }
+                (b'+', _) => {
+                    ret.push(b' ');
+                    x += 1;
+                }
+                (v, _) => {
+                    ret.push(v);
+                    x += 1;
+                }
+            }
+        }
+
+        String::from_utf8_lossy(&ret).to_string()
+    };
+
+    body.split('&')
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            let (k, v) = v.split_once('=').unwrap_or((v, ""));
+            (dec(k), dec(v))
+        })
+        .collect()
+}
+
+/* Plain for http:// urls, curl for anything else */
+pub fn backend(url: &str) -> Box<dyn Http> {
+    if url.starts_with("http://") {
+        Box::new(Plain)
+    } else {
+        Box::new(Curl)
+    }
+}
+
+impl<H: Http + ?Sized> Http for Box<H> {
+    fn send(&self, req: &Request) -> Result<Response, HttpError> {
+        (**self).send(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_port() {
+        assert_eq!(address("localhost"), "localhost:80");
+        assert_eq!(address("127.0.0.1:8080"), "127.0.0.1:8080");
+        assert_eq!(address("[::1]"), "[::1]:80");
+        assert_eq!(address("[::1]:8080"), "[::1]:8080");
+    }
+
+    #[test]
+    fn form_round_trip() {
+        let pairs = [("level", "1"), ("answer", "a b&c=d%")];
+        let body = form_encode(&pairs);
+
+        assert_eq!(body, "level=1&answer=a%20b%26c%3Dd%25");
+        assert_eq!(
+            form_decode(&body),
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/* Streaming reader over line oriented inputs, only one line
+is held in memory at a time. Lines come without their "\n" or
+"\r\n" ending and a missing final newline makes no difference */
+pub struct LineReader<R> {
+    r: R,
+    buf: String,
+}
+
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<LineReader<BufReader<File>>> {
+    Ok(LineReader::new(BufReader::new(File::open(path)?)))
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(r: R) -> LineReader<R> {
+        LineReader {
+            r,
+            buf: String::new(),
+        }
+    }
+
+    /* Borrowing version reusing the same buffer for every line */
+    pub fn next_line(&mut self) -> io::Result<Option<&str>> {
+        self.buf.clear();
+
+        if self.r.read_line(&mut self.buf)? == 0 {
+            return Ok(None);
+        }
+
+        let l = self.buf.strip_suffix('\n').unwrap_or(&self.buf);
+        let l = l.strip_suffix('\r').unwrap_or(l);
+
+        Ok(Some(l))
+    }
+
+    pub fn lines(self) -> Lines<R> {
+        Lines { r: self }
+    }
+
+    pub fn blocks(self) -> Blocks<R> {
+        Blocks { r: self }
+    }
+}
+
+pub struct Lines<R> {
+    r: LineReader<R>,
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.r
+            .next_line()
+            .map(|v| v.map(str::to_string))
+            .transpose()
+    }
+}
+
+/* Groups of lines separated by one or more blank lines */
+pub struct Blocks<R> {
+    r: LineReader<R>,
+}
+
+impl<R: BufRead> Iterator for Blocks<R> {
+    type Item = io::Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut ret: Vec<String> = Vec::new();
+
+        loop {
+            match self.r.next_line() {
+                Err(e) => return Some(Err(e)),
+                Ok(None) => break,
+                Ok(Some(l)) if l.trim().is_empty() => {
+                    if !ret.is_empty() {
+                        break;
+                    }
+                }
+                Ok(Some(l)) => ret.push(l.to_string()),
+            }
+        }
+
+        if ret.is_empty() {
+            None
+        } else {
+            Some(Ok(ret))
+        }
+    }
+}
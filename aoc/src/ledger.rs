@@ -0,0 +1,197 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::parse::{ParseError, Parser};
+
+/* What the site said about a submitted answer */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /* Wrong without a hint */
+    Wrong,
+}
+
+impl Verdict {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::Wrong => "wrong",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Verdict> {
+        [
+            Verdict::Correct,
+            Verdict::TooHigh,
+            Verdict::TooLow,
+            Verdict::Wrong,
+        ]
+        .into_iter()
+        .find(|v| v.name() == s)
+    }
+
+    /* Reads the page answering a submission, None when it is not a
+    verdict, such as when answering too soon or the wrong part */
+    pub fn from_page(page: &str) -> Option<Verdict> {
+        if page.contains("That's the right answer") {
+            Some(Verdict::Correct)
+        } else if !page.contains("That's not the right answer") {
+            None
+        } else if page.contains("too high") {
+            Some(Verdict::TooHigh)
+        } else if page.contains("too low") {
+            Some(Verdict::TooLow)
+        } else {
+            Some(Verdict::Wrong)
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/* One submission, at is in seconds since the epoch */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+    pub at: u64,
+}
+
+/* Every answer submitted for a day, one "PART VERDICT TIME ANSWER"
+line each, kept next to data.txt as answers.txt */
+pub struct Ledger {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl Ledger {
+    /* A missing file is an empty ledger */
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Ledger, ParseError> {
+        let path = path.as_ref().to_path_buf();
+        let text = fs::read_to_string(&path).unwrap_or_default();
+
+        let mut entries = Vec::new();
+
+        for (x, l) in text.lines().enumerate() {
+            if l.trim().is_empty() || l.starts_with('#') {
+                continue;
+            }
+
+            let mut p = Parser::new(l);
+
+            let part = p.number().map_err(|e| e.at_line(x + 1))?;
+            let word = p.token().map_err(|e| e.at_line(x + 1))?;
+            let verdict = Verdict::parse(word)
+                .ok_or_else(|| p.error(format!("unknown verdict {}", word)).at_line(x + 1))?;
+            let at = p.number().map_err(|e| e.at_line(x + 1))?;
+            p.skip_ws();
+
+            entries.push(Entry {
+                part,
+                answer: p.rest().to_string(),
+                verdict,
+                at,
+            });
+        }
+
+        Ok(Ledger { path, entries })
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn correct(&self, part: u8) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|e| e.part == part && e.verdict == Verdict::Correct)
+    }
+
+    /* What is already known about answer without submitting it: its own
+    verdict, wrong when another one was correct, or too high/low when
+    it is past a number the site already called too high/low */
+    pub fn judge(&self, part: u8, answer: &str) -> Option<Verdict> {
+        let answer = answer.trim();
+        let entries = self.entries.iter().filter(|e| e.part == part);
+
+        if let Some(e) = entries.clone().find(|e| e.answer == answer) {
+            return Some(e.verdict);
+        }
+
+        if self.correct(part).is_some() {
+            return Some(Verdict::Wrong);
+        }
+
+        let n: i128 = answer.parse().ok()?;
+
+        entries
+            .filter_map(|e| Some((e.verdict, e.answer.parse::<i128>().ok()?)))
+            .find_map(|(v, bound)| match v {
+                Verdict::TooHigh if n >= bound => Some(v),
+                Verdict::TooLow if n <= bound => Some(v),
+                _ => None,
+            })
+    }
+
+    /* Appends the submission to the file right away */
+    pub fn record(&mut self, part: u8, answer: &str, verdict: Verdict) -> io::Result<&Entry> {
+        let e = Entry {
+            part,
+            answer: answer.trim().to_string(),
+            verdict,
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(f, "{} {} {} {}", e.part, e.verdict, e.at, e.answer)?;
+
+        self.entries.push(e);
+        Ok(self.entries.last().unwrap())
+    }
+}
+
+/* Prints a day's answer, warning when answers.txt knows better,
+such as a refactor breaking an answer that was accepted */
+pub fn answer<T: fmt::Display>(part: u8, value: T) {
+    let value = value.to_string();
+
+    println!("Part {} : {}", part, value);
+
+    let ledger = match Ledger::open("answers.txt") {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Warning: answers.txt {}", e);
+            return;
+        }
+    };
+
+    match (ledger.judge(part, &value), ledger.correct(part)) {
+        (Some(Verdict::Correct), _) | (None, _) => (),
+        (Some(_), Some(e)) => eprintln!(
+            "Warning: part {} answer {} differs from the correct {}",
+            part, value, e.answer
+        ),
+        (Some(v), None) => eprintln!(
+            "Warning: part {} answer {} is known to be {}",
+            part, value, v
+        ),
+    }
+}
@@ -0,0 +1,15 @@
+pub mod args;
+pub mod client;
+pub mod diff;
+pub mod geom;
+pub mod grid;
+pub mod http;
+pub mod input;
+pub mod ledger;
+pub mod math;
+pub mod parse;
+pub mod rng;
+pub mod scaffold;
+pub mod search;
+pub mod solution;
+pub mod stub;
@@ -0,0 +1,189 @@
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use aoc::client::{Client, Config, Submission};
+use aoc::http;
+use aoc::ledger::Ledger;
+use aoc::scaffold;
+use aoc::stub::Stub;
+
+/* Value following flag on the command line */
+fn flag(args: &[String], flag: &str) -> Option<String> {
+    args.iter().position(|v| v == flag).map(|x| {
+        args.get(x + 1)
+            .unwrap_or_else(|| panic!("{} needs a value", flag))
+            .clone()
+    })
+}
+
+/* Arguments after the command that are neither a flag nor a flag value */
+fn positional(args: &[String]) -> Vec<&String> {
+    let mut ret = Vec::new();
+    let mut it = args.iter().skip(1);
+
+    while let Some(a) = it.next() {
+        if a.starts_with("--") {
+            it.next();
+        } else {
+            ret.push(a);
+        }
+    }
+
+    ret
+}
+
+fn number(args: &[String], name: &str) -> Option<u32> {
+    flag(args, name).map(|v| {
+        v.parse()
+            .unwrap_or_else(|_| panic!("{} takes a number, got {}", name, v))
+    })
+}
+
+fn year(args: &[String]) -> u32 {
+    number(args, "--year")
+        .or_else(|| std::env::var("AOC_YEAR").ok()?.parse().ok())
+        .unwrap_or(2023)
+}
+
+/* Caches the input then puts it in dN/data.txt when that day
+exists and has no input yet */
+fn fetch(args: &[String]) {
+    let day = number(args, "--day").expect("fetch needs --day N");
+    let year = year(args);
+
+    let cfg = Config::load();
+    let client = Client::new(http::backend(&cfg.url), cfg);
+
+    let data = match client.input(year, day) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Cannot get {} day {}: {}", year, day, e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", client.input_path(year, day).display());
+
+    let dest = PathBuf::from(format!("d{}", day)).join("data.txt");
+
+    if dest.parent().unwrap().is_dir() && !dest.exists() {
+        std::fs::write(&dest, data).unwrap();
+        println!("{}", dest.display());
+    }
+}
+
+/* Submits an answer unless dN/answers.txt already tells its
+verdict, the one given by the site is added to that file */
+fn submit(args: &[String]) {
+    let day = number(args, "--day").expect("submit needs --day N");
+    let part = number(args, "--part").expect("submit needs --part P") as u8;
+    let year = year(args);
+    let answer = *positional(args).first().expect("submit needs an answer");
+
+    let path = PathBuf::from(format!("d{}", day)).join("answers.txt");
+    let mut ledger = Ledger::open(&path).unwrap();
+
+    let cfg = Config::load();
+    let client = Client::new(http::backend(&cfg.url), cfg);
+
+    match client.submit_once(&mut ledger, year, day, part, answer) {
+        Ok(Submission::Known(v)) => println!("Not submitting, {} is known to be {}", answer, v),
+        Ok(Submission::Sent(v)) => println!("{} is {}", answer.trim(), v),
+        Err(e) => {
+            eprintln!("Cannot submit {} day {} part {}: {}", year, day, part, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/* Scaffolds dN from the templates and registers it for run */
+fn new(args: &[String]) {
+    let day = number(args, "--day").expect("new needs --day N");
+    let templates = flag(args, "--templates")
+        .or_else(|| std::env::var("AOC_TEMPLATES").ok())
+        .map(PathBuf::from);
+
+    match scaffold::new_day(Path::new("."), templates.as_deref(), year(args), day) {
+        Ok(dir) => {
+            println!("{}", dir.display());
+            println!("Get its input with aoc fetch --day {}", day);
+        }
+        Err(e) => {
+            eprintln!("Cannot create day {}: {}", day, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/* Runs every registered day, or only the one given, in release mode */
+fn run(args: &[String]) {
+    let only = number(args, "--day").map(|d| format!("d{}", d));
+    let mut failed = Vec::new();
+
+    let days = match scaffold::registered(Path::new(".")) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Cannot read {}: {}", scaffold::REGISTRY, e);
+            std::process::exit(1);
+        }
+    };
+
+    for d in days {
+        if only.as_ref().is_some_and(|o| *o != d) {
+            continue;
+        }
+
+        println!("== {}", d);
+
+        let ok = Command::new("cargo")
+            .args(["run", "-q", "--release"])
+            .current_dir(&d)
+            .status()
+            .is_ok_and(|s| s.success());
+
+        if !ok {
+            failed.push(d);
+        }
+    }
+
+    if !failed.is_empty() {
+        eprintln!("Failed: {}", failed.join(" "));
+        std::process::exit(1);
+    }
+}
+
+fn stub(args: &[String]) {
+    let port = number(args, "--port").unwrap_or(8023);
+    let dir = flag(args, "--dir").unwrap_or_else(|| "stub".to_string());
+
+    let l = TcpListener::bind(("127.0.0.1", port as u16)).unwrap();
+    println!("Serving {} on http://127.0.0.1:{}", dir, port);
+
+    Stub::new(Path::new(&dir)).serve(l).unwrap();
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    /* aoc fetch --day N [--year Y]    downloads an input once, see Config::load
+    aoc submit --day N --part P [--year Y] ANSWER    records the verdict in dN/answers.txt
+    aoc new --day N [--year Y] [--templates DIR]    scaffolds dN and adds it to days.txt
+    aoc run [--day N]    runs the days listed in days.txt
+    aoc stub [--port P] [--dir D]    serves D/YEAR/dayN.txt, point AOC_URL to it */
+    match args.first().map(String::as_str) {
+        Some("fetch") => fetch(&args),
+        Some("submit") => submit(&args),
+        Some("new") => new(&args),
+        Some("run") => run(&args),
+        Some("stub") => stub(&args),
+        _ => {
+            eprintln!("usage: aoc fetch --day N [--year Y]");
+            eprintln!("       aoc submit --day N --part P [--year Y] ANSWER");
+            eprintln!("       aoc new --day N [--year Y] [--templates DIR]");
+            eprintln!("       aoc run [--day N]");
+            eprintln!("       aoc stub [--port P] [--dir D]");
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+/* Integer helpers for cycle and bound computations, everything
+that may not fit returns None instead of wrapping */
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/* lcm(0, x) is 0 */
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/* 0 for an empty slice */
+pub fn gcd_all(v: &[u64]) -> u64 {
+    v.iter().fold(0, |a, b| gcd(a, *b))
+}
+
+/* 1 for an empty slice */
+pub fn lcm_all(v: &[u64]) -> Option<u64> {
+    v.iter().try_fold(1, |a, b| lcm(a, *b))
+}
+
+/* (g, x, y) with a * x + b * y = g = gcd(a, b), g is never negative.
+Results are i128 as gcd(i64::MIN, 0) = 2^63 does not fit in i64 */
+pub fn ext_gcd(a: i64, b: i64) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a as i128, b as i128);
+    let (mut x0, mut x1) = (1i128, 0i128);
+    let (mut y0, mut y1) = (0i128, 1i128);
+
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+
+    if r0 < 0 {
+        (r0, x0, y0) = (-r0, -x0, -y0);
+    }
+
+    (r0, x0, y0)
+}
+
+/* x with a * x = 1 mod m, only when a and m are coprime */
+pub fn mod_inv(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m as i128) as i64)
+}
+
+/* base^exp mod m, m must not be 0 */
+pub fn mod_pow(base: u64, exp: u64, m: u64) -> u64 {
+    assert!(m > 0, "modulus 0");
+
+    if m == 1 {
+        return 0;
+    }
+
+    let m = m as u128;
+    let mut b = base as u128 % m;
+    let mut e = exp;
+    let mut ret: u128 = 1;
+
+    while e > 0 {
+        if e & 1 == 1 {
+            ret = ret * b % m;
+        }
+        b = b * b % m;
+        e >>= 1;
+    }
+
+    ret as u64
+}
+
+/* Merges x = r (mod m) pairs into one (r, lcm of the moduli) with
+0 <= r < lcm. Moduli need not be coprime, None when the congruences
+contradict each other or the combined modulus does not fit in i64 */
+pub fn crt(v: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut r: i128 = 0;
+    let mut m: i128 = 1;
+
+    for (ri, mi) in v.iter() {
+        if *mi <= 0 {
+            return None;
+        }
+
+        let (ri, mi) = ((*ri as i128).rem_euclid(*mi as i128), *mi as i128);
+
+        /* r + m * k = ri (mod mi) solved for k */
+        let (g, p, _) = ext_gcd(m as i64, mi as i64);
+
+        if (ri - r) % g != 0 {
+            return None;
+        }
+
+        let step = mi / g;
+        let k = ((ri - r) / g % step) * (p % step) % step;
+
+        r += m * k;
+        m *= step;
+
+        if m > i64::MAX as i128 {
+            return None;
+        }
+        r = r.rem_euclid(m);
+    }
+
+    Some((r as i64, m as i64))
+}
+
+/* Largest x with x^k <= n */
+pub fn iroot(n: u64, k: u32) -> u64 {
+    assert!(k > 0, "0th root");
+
+    if k == 1 || n < 2 {
+        return n;
+    }
+
+    /* Float estimate then fixed up as it may be off by a few units */
+    let mut x = (n as f64).powf(1.0 / k as f64) as u64;
+
+    while x.checked_pow(k).is_none_or(|v| v > n) {
+        x -= 1;
+    }
+    while (x + 1).checked_pow(k).is_some_and(|v| v <= n) {
+        x += 1;
+    }
+
+    x
+}
+
+pub fn isqrt(n: u64) -> u64 {
+    iroot(n, 2)
+}
+
+pub fn is_square(n: u64) -> bool {
+    let r = isqrt(n);
+    r * r == n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /* Random values of any magnitude, small ones as likely as big ones */
+    fn any(rng: &mut Rng) -> u64 {
+        rng.next_u64() >> rng.below(64)
+    }
+
+    fn signed(rng: &mut Rng) -> i64 {
+        let v = any(rng) as i64;
+        if rng.chance(50) {
+            v.wrapping_neg()
+        } else {
+            v
+        }
+    }
+
+    fn brute_gcd(a: u64, b: u64) -> u64 {
+        (1..=a.max(b))
+            .rev()
+            .find(|d| a.is_multiple_of(*d) && b.is_multiple_of(*d))
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn gcd_lcm() {
+        let mut rng = Rng::new(1);
+
+        for _ in 0..1000 {
+            let (a, b) = (rng.below(200), rng.below(200));
+            let g = gcd(a, b);
+
+            assert_eq!(g, brute_gcd(a, b), "gcd({}, {})", a, b);
+
+            let l = (1..=a * b).find(|v| v % a == 0 && v % b == 0).unwrap_or(0);
+            assert_eq!(lcm(a, b), Some(l), "lcm({}, {})", a, b);
+        }
+
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(gcd_all(&[]), 0);
+        assert_eq!(lcm_all(&[]), Some(1));
+        assert_eq!(lcm_all(&[4, 6, 10]), Some(60));
+    }
+
+    #[test]
+    fn ext_gcd_bezout() {
+        let mut rng = Rng::new(2);
+
+        let mut cases = vec![
+            (i64::MIN, 0),
+            (0, i64::MIN),
+            (i64::MIN, i64::MIN),
+            (i64::MIN, i64::MAX),
+            (i64::MAX, i64::MAX),
+            (0, 0),
+            (-4, 0),
+            (0, -4),
+        ];
+        cases.extend((0..10000).map(|_| (signed(&mut rng), signed(&mut rng))));
+
+        for (a, b) in cases {
+            let (g, x, y) = ext_gcd(a, b);
+
+            assert!(g >= 0, "ext_gcd({}, {}) = {}", a, b, g);
+            assert_eq!(g as u128, gcd(a.unsigned_abs(), b.unsigned_abs()) as u128);
+            assert_eq!(a as i128 * x + b as i128 * y, g, "ext_gcd({}, {})", a, b);
+        }
+
+        assert_eq!(ext_gcd(i64::MIN, 0).0, 1 << 63);
+    }
+
+    #[test]
+    fn mod_ops() {
+        let mut rng = Rng::new(3);
+
+        for _ in 0..2000 {
+            let m = 1 + rng.below(100) as i64;
+            let a = signed(&mut rng);
+
+            let brute =
+                (0..m).find(|x| (a as i128 * *x as i128).rem_euclid(m as i128) == 1 % m as i128);
+            assert_eq!(mod_inv(a, m), brute, "mod_inv({}, {})", a, m);
+
+            let (base, exp, m) = (any(&mut rng), rng.below(50), 1 + any(&mut rng));
+            let brute = (0..exp).fold(1 % m as u128, |v, _| v * base as u128 % m as u128);
+            assert_eq!(
+                mod_pow(base, exp, m) as u128,
+                brute,
+                "{}^{} mod {}",
+                base,
+                exp,
+                m
+            );
+        }
+
+        assert_eq!(mod_inv(3, 0), None);
+        assert_eq!(mod_inv(3, -7), None);
+        assert_eq!(mod_pow(0, 0, 1), 0);
+        assert_eq!(mod_pow(u64::MAX, u64::MAX, u64::MAX), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus 0")]
+    fn mod_pow_zero() {
+        mod_pow(2, 3, 0);
+    }
+
+    #[test]
+    fn crt_brute_force() {
+        let mut rng = Rng::new(4);
+
+        for _ in 0..2000 {
+            let v: Vec<(i64, i64)> = (0..1 + rng.below(3))
+                .map(|_| (rng.range(-50..50), 1 + rng.below(12) as i64))
+                .collect();
+
+            let m = lcm_all(&v.iter().map(|p| p.1 as u64).collect::<Vec<u64>>()).unwrap() as i64;
+            let brute = (0..m)
+                .find(|x| v.iter().all(|(r, mi)| (x - r).rem_euclid(*mi) == 0))
+                .map(|x| (x, m));
+
+            assert_eq!(crt(&v), brute, "crt({:?})", v);
+        }
+
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(crt(&[(0, i64::MAX), (0, i64::MAX - 1)]), None);
+    }
+
+    #[test]
+    fn roots() {
+        let mut rng = Rng::new(5);
+
+        for _ in 0..10000 {
+            let n = any(&mut rng);
+            let k = 1 + rng.below(8) as u32;
+            let r = iroot(n, k);
+
+            assert!(
+                r.checked_pow(k).is_some_and(|v| v <= n),
+                "iroot({}, {})",
+                n,
+                k
+            );
+            assert!(
+                (r + 1).checked_pow(k).is_none_or(|v| v > n),
+                "iroot({}, {})",
+                n,
+                k
+            );
+        }
+
+        for n in 0..2000u64 {
+            let brute = (0..=n).take_while(|x| x * x <= n).last().unwrap();
+            assert_eq!(isqrt(n), brute);
+            assert_eq!(is_square(n), brute * brute == n);
+        }
+
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert!(is_square((u32::MAX as u64) * (u32::MAX as u64)));
+    }
+}
@@ -0,0 +1,410 @@
+use std::fmt;
+use std::str::FromStr;
+
+/* What went wrong and where, col is a byte offset in the line
+starting at 1, line is only known once the caller sets it */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub col: usize,
+    pub msg: String,
+}
+
+impl ParseError {
+    /* Line number, starting at 1, of the parsed text */
+    pub fn at_line(mut self, line: usize) -> ParseError {
+        self.line = Some(line);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(l) = self.line {
+            write!(f, "line {} ", l)?;
+        }
+        write!(f, "col {}: {}", self.col, self.msg)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/* Cursor over a line, a parser made by splitting another one
+keeps reporting errors at offsets of the whole line */
+#[derive(Debug, Clone, Copy)]
+pub struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(s: &'a str) -> Parser<'a> {
+        Parser {
+            s,
+            pos: 0,
+            end: s.len(),
+        }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.s[self.pos..self.end]
+    }
+
+    /* Only whitespace left */
+    pub fn is_done(&self) -> bool {
+        self.rest().trim().is_empty()
+    }
+
+    pub fn error(&self, msg: impl Into<String>) -> ParseError {
+        ParseError {
+            line: None,
+            col: self.pos + 1,
+            msg: msg.into(),
+        }
+    }
+
+    pub fn skip_ws(&mut self) {
+        let r = self.rest();
+        self.pos += r.len() - r.trim_start().len();
+    }
+
+    /* Takes the next n bytes */
+    fn take(&mut self, n: usize) -> &'a str {
+        let ret = &self.s[self.pos..self.pos + n];
+        self.pos += n;
+        ret
+    }
+
+    /* Consumes t, after any whitespace, if it comes next */
+    pub fn eat(&mut self, t: &str) -> bool {
+        let save = self.pos;
+        self.skip_ws();
+
+        if self.rest().starts_with(t) {
+            self.pos += t.len();
+            true
+        } else {
+            self.pos = save;
+            false
+        }
+    }
+
+    pub fn tag(&mut self, t: &str) -> Result<(), ParseError> {
+        if self.eat(t) {
+            return Ok(());
+        }
+
+        self.skip_ws();
+        Err(self.error(format!("expected '{}'", t)))
+    }
+
+    /* Run of bytes matching f after any whitespace */
+    fn span(&mut self, f: impl Fn(char) -> bool, what: &str) -> Result<&'a str, ParseError> {
+        self.skip_ws();
+
+        let len = self.rest().find(|c| !f(c)).unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.error(format!("expected {}", what)));
+        }
+
+        Ok(self.take(len))
+    }
+
+    /* Letters and digits, such as a name or a label */
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        self.span(|c| c.is_ascii_alphanumeric(), "a word")
+    }
+
+    /* Anything up to the next whitespace */
+    pub fn token(&mut self) -> Result<&'a str, ParseError> {
+        self.span(|c| !c.is_whitespace(), "a token")
+    }
+
+    /* Optionally signed decimal number */
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        self.skip_ws();
+
+        let r = self.rest();
+        let sign = usize::from(r.starts_with(['-', '+']));
+        let len = r[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(r.len() - sign);
+
+        if len == 0 {
+            return Err(self.error("expected a number"));
+        }
+
+        let at = *self;
+        self.take(sign + len)
+            .parse()
+            .map_err(|_| at.error("bad number"))
+    }
+
+    /* Whitespace separated numbers up to the end */
+    pub fn numbers<T: FromStr>(&mut self) -> Result<Vec<T>, ParseError> {
+        let mut ret = Vec::new();
+
+        while !self.is_done() {
+            ret.push(self.number()?);
+        }
+
+        Ok(ret)
+    }
+
+    /* Items separated by sep up to the end, "1,1,3" or "3 blue, 4 red" */
+    pub fn list<T>(
+        &mut self,
+        sep: &str,
+        mut f: impl FnMut(&mut Parser<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut ret = Vec::new();
+
+        for mut p in self.split(sep) {
+            ret.push(f(&mut p)?);
+            p.end()?;
+        }
+
+        Ok(ret)
+    }
+
+    /* Parts around each sep, all of what is left is consumed */
+    pub fn split(&mut self, sep: &str) -> Vec<Parser<'a>> {
+        let mut ret = Vec::new();
+        let mut start = self.pos;
+
+        while let Some(x) = self.s[start..self.end].find(sep) {
+            ret.push(Parser {
+                s: self.s,
+                pos: start,
+                end: start + x,
+            });
+            start += x + sep.len();
+        }
+
+        ret.push(Parser {
+            s: self.s,
+            pos: start,
+            end: self.end,
+        });
+
+        self.pos = self.end;
+        ret
+    }
+
+    /* Parts before and after the first sep, "a | b" */
+    pub fn split_once(&mut self, sep: &str) -> Result<(Parser<'a>, Parser<'a>), ParseError> {
+        let x = match self.rest().find(sep) {
+            Some(x) => self.pos + x,
+            None => return Err(self.error(format!("expected '{}'", sep))),
+        };
+
+        let left = Parser {
+            s: self.s,
+            pos: self.pos,
+            end: x,
+        };
+        let right = Parser {
+            s: self.s,
+            pos: x + sep.len(),
+            end: self.end,
+        };
+
+        self.pos = self.end;
+        Ok((left, right))
+    }
+
+    /* Fails if anything but whitespace is left */
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_ws();
+
+        if self.pos < self.end {
+            return Err(self.error(format!("unexpected '{}'", self.rest())));
+        }
+
+        Ok(())
+    }
+}
+
+/* "Card 12: ..." gives 12 and a parser on what follows the colon */
+pub fn header<'a, T: FromStr>(line: &'a str, label: &str) -> Result<(T, Parser<'a>), ParseError> {
+    let mut p = Parser::new(line);
+    p.tag(label)?;
+    let id = p.number()?;
+    p.tag(":")?;
+
+    Ok((id, p))
+}
+
+/* "Time: ..." gives a parser on what follows the colon */
+pub fn labelled<'a>(line: &'a str, label: &str) -> Result<Parser<'a>, ParseError> {
+    let mut p = Parser::new(line);
+    p.tag(label)?;
+    p.tag(":")?;
+
+    Ok(p)
+}
+
+/* Whitespace separated signed numbers making the whole line */
+pub fn numbers<T: FromStr>(line: &str) -> Result<Vec<T>, ParseError> {
+    Parser::new(line).numbers()
+}
+
+/* "AAA = (BBB, CCC)" gives AAA and [BBB, CCC] */
+pub fn keyed_tuple(line: &str) -> Result<(&str, Vec<&str>), ParseError> {
+    let mut p = Parser::new(line);
+
+    let key = p.word()?;
+    p.tag("=")?;
+    p.tag("(")?;
+
+    let (mut inner, mut tail) = p.split_once(")")?;
+    tail.end()?;
+
+    let values = inner.list(",", |v| v.word())?;
+
+    Ok((key, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* (col, msg) of an error, col counts from 1 */
+    fn at(e: ParseError) -> (usize, String) {
+        (e.col, e.msg)
+    }
+
+    #[test]
+    fn error_display() {
+        let e = Parser::new("abc").error("oops");
+        assert_eq!(e.line, None);
+        assert_eq!(e.to_string(), "col 1: oops");
+        assert_eq!(e.at_line(4).to_string(), "line 4 col 1: oops");
+    }
+
+    #[test]
+    fn columns() {
+        let mut p = Parser::new("  12 x");
+        assert_eq!(p.number::<u32>(), Ok(12));
+        assert_eq!(
+            at(p.number::<u32>().unwrap_err()),
+            (6, "expected a number".to_string())
+        );
+
+        let mut p = Parser::new("7 300");
+        p.number::<u8>().unwrap();
+        assert_eq!(
+            at(p.number::<u8>().unwrap_err()),
+            (3, "bad number".to_string())
+        );
+
+        let mut p = Parser::new("ab  cd");
+        p.word().unwrap();
+        assert_eq!(at(p.tag(":").unwrap_err()), (5, "expected ':'".to_string()));
+
+        let mut p = Parser::new("1 2 x ");
+        assert_eq!(at(p.numbers::<i32>().unwrap_err()).0, 5);
+
+        let mut p = Parser::new("ab cd");
+        p.word().unwrap();
+        assert_eq!(at(p.end().unwrap_err()), (4, "unexpected 'cd'".to_string()));
+
+        let mut p = Parser::new("-5 +6 -");
+        assert_eq!(p.number::<i32>(), Ok(-5));
+        assert_eq!(p.number::<i32>(), Ok(6));
+        assert_eq!(at(p.number::<i32>().unwrap_err()).0, 7);
+    }
+
+    /* Errors in the parts keep the offsets of the whole line */
+    #[test]
+    fn split_offsets() {
+        let mut p = Parser::new("1,22, x,4");
+        let parts = p.split(",");
+
+        assert!(p.is_done());
+        assert_eq!(
+            parts.iter().map(|v| v.rest()).collect::<Vec<&str>>(),
+            ["1", "22", " x", "4"]
+        );
+        assert_eq!(at(parts[2].clone().number::<u32>().unwrap_err()).0, 7);
+
+        let e = Parser::new("1,22, x,4")
+            .list(",", |v| v.number::<u32>())
+            .unwrap_err();
+        assert_eq!(e.col, 7);
+
+        let e = Parser::new("1 ,2 3")
+            .list(",", |v| v.number::<u32>())
+            .unwrap_err();
+        assert_eq!(at(e), (6, "unexpected '3'".to_string()));
+    }
+
+    #[test]
+    fn split_once_offsets() {
+        let mut p = Parser::new("41 48 | 83 x");
+        let (mut l, mut r) = p.split_once("|").unwrap();
+
+        assert_eq!(l.numbers::<u32>(), Ok(vec![41, 48]));
+        assert_eq!(at(r.numbers::<u32>().unwrap_err()).0, 12);
+
+        let mut p = Parser::new("41 48");
+        p.number::<u32>().unwrap();
+        assert_eq!(
+            at(p.split_once("|").unwrap_err()),
+            (3, "expected '|'".to_string())
+        );
+    }
+
+    #[test]
+    fn header_lines() {
+        let (id, mut p) = header::<u32>("Card  12: 1 2", "Card").unwrap();
+        assert_eq!(id, 12);
+        assert_eq!(p.numbers::<u32>(), Ok(vec![1, 2]));
+
+        assert_eq!(at(header::<u32>("Game x: 1", "Game").unwrap_err()).0, 6);
+        assert_eq!(
+            at(header::<u32>("Game 3 1", "Game").unwrap_err()),
+            (8, "expected ':'".to_string())
+        );
+        assert_eq!(
+            at(header::<u32>("Card 3:", "Game").unwrap_err()),
+            (1, "expected 'Game'".to_string())
+        );
+    }
+
+    #[test]
+    fn labelled_lines() {
+        let mut p = labelled("Time:  7  15", "Time").unwrap();
+        assert_eq!(p.numbers::<u32>(), Ok(vec![7, 15]));
+
+        assert_eq!(at(labelled("Time 7", "Time").unwrap_err()).0, 6);
+        assert_eq!(at(labelled("Distance: 7", "Time").unwrap_err()).0, 1);
+    }
+
+    #[test]
+    fn keyed_tuples() {
+        assert_eq!(
+            keyed_tuple("AAA = (BBB, CCC)"),
+            Ok(("AAA", vec!["BBB", "CCC"]))
+        );
+        assert_eq!(keyed_tuple("11A = (11B)"), Ok(("11A", vec!["11B"])));
+
+        assert_eq!(
+            at(keyed_tuple("AAA (BBB, CCC)").unwrap_err()),
+            (5, "expected '='".to_string())
+        );
+        assert_eq!(
+            at(keyed_tuple("AAA = (BBB, CCC").unwrap_err()).1,
+            "expected ')'"
+        );
+        assert_eq!(
+            at(keyed_tuple("AAA = (BBB, CCC) x").unwrap_err()),
+            (18, "unexpected 'x'".to_string())
+        );
+        assert_eq!(
+            at(keyed_tuple("AAA = (BBB, ?)").unwrap_err()),
+            (13, "expected a word".to_string())
+        );
+    }
+}
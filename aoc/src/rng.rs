@@ -0,0 +1,62 @@
+use std::ops::Range;
+
+/* SplitMix64, small and good enough for generating inputs. It is
+kept in the crate so a seed gives the same output on any machine
+and with any version of the dependencies */
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /* Uniform in 0..n, n must not be 0 */
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "empty range");
+
+        /* Rejecting the top values removes the modulo bias */
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let v = self.next_u64();
+            if v < zone {
+                return v % n;
+            }
+        }
+    }
+
+    pub fn range(&mut self, r: Range<i64>) -> i64 {
+        assert!(r.start < r.end, "empty range");
+        r.start + self.below(r.end.abs_diff(r.start)) as i64
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    /* True with a probability of percent / 100 */
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, v: &'a [T]) -> &'a T {
+        &v[self.index(v.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, v: &mut [T]) {
+        for x in (1..v.len()).rev() {
+            v.swap(x, self.index(x + 1));
+        }
+    }
+}
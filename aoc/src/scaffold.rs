@@ -0,0 +1,158 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/* Built in templates, a file of the same name in the
+templates directory given to new replaces one */
+const TEMPLATES: [(&str, &str); 2] = [
+    (
+        "Cargo.toml.tmpl",
+        include_str!("../templates/Cargo.toml.tmpl"),
+    ),
+    ("main.rs.tmpl", include_str!("../templates/main.rs.tmpl")),
+];
+
+/* Days the runner knows about, one directory per line */
+pub const REGISTRY: &str = "days.txt";
+
+fn template(dir: Option<&Path>, name: &str) -> io::Result<String> {
+    if let Some(t) = dir.map(|d| d.join(name)).filter(|p| p.exists()) {
+        return fs::read_to_string(t);
+    }
+
+    let (_, text) = TEMPLATES.iter().find(|t| t.0 == name).unwrap();
+    Ok(text.to_string())
+}
+
+/* Replaces {{day}} and {{year}} */
+pub fn render(text: &str, year: u32, day: u32) -> String {
+    text.replace("{{day}}", &day.to_string())
+        .replace("{{year}}", &year.to_string())
+}
+
+/* No registry yet means no days, any other read error is returned */
+pub fn registered(root: &Path) -> io::Result<Vec<String>> {
+    let text = match fs::read_to_string(root.join(REGISTRY)) {
+        Ok(t) => t,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/* Creates the dN crate under root and registers it,
+an existing directory is never touched */
+pub fn new_day(root: &Path, templates: Option<&Path>, year: u32, day: u32) -> io::Result<PathBuf> {
+    let name = format!("d{}", day);
+    let dir = root.join(&name);
+
+    if dir.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dir.display()),
+        ));
+    }
+
+    fs::create_dir_all(dir.join("src"))?;
+
+    for (file, tmpl) in [
+        ("Cargo.toml", "Cargo.toml.tmpl"),
+        ("src/main.rs", "main.rs.tmpl"),
+    ] {
+        fs::write(
+            dir.join(file),
+            render(&template(templates, tmpl)?, year, day),
+        )?;
+    }
+
+    if !registered(root)?.contains(&name) {
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(root.join(REGISTRY))?;
+        writeln!(f, "{}", name)?;
+    }
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("aoc-scaffold-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn registry() {
+        let root = scratch("registry");
+        assert!(registered(&root).unwrap().is_empty());
+
+        fs::write(root.join(REGISTRY), "d1\n\n# d2\n  d3 \n").unwrap();
+        assert_eq!(registered(&root).unwrap(), ["d1", "d3"]);
+
+        /* Unreadable rather than missing */
+        fs::remove_file(root.join(REGISTRY)).unwrap();
+        fs::create_dir(root.join(REGISTRY)).unwrap();
+        assert!(registered(&root).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn new_days() {
+        let root = scratch("new");
+        fs::write(root.join(REGISTRY), "d1\n").unwrap();
+
+        let dir = new_day(&root, None, 2023, 7).unwrap();
+        assert_eq!(dir, root.join("d7"));
+
+        let toml = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert!(toml.contains("name = \"d7\""), "{}", toml);
+        let main = fs::read_to_string(dir.join("src/main.rs")).unwrap();
+        assert!(main.contains("struct Day7 {"), "{}", main);
+        assert!(main.contains("Advent of Code 2023 day 7"), "{}", main);
+        assert!(!main.contains("{{"), "{}", main);
+
+        assert_eq!(registered(&root).unwrap(), ["d1", "d7"]);
+
+        /* An existing day is left as it is */
+        fs::write(dir.join("src/main.rs"), "mine").unwrap();
+        let e = new_day(&root, None, 2023, 7).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(dir.join("src/main.rs")).unwrap(), "mine");
+        assert_eq!(registered(&root).unwrap(), ["d1", "d7"]);
+
+        /* Templates found in the given directory win, the others are built in */
+        let templates = root.join("templates");
+        fs::create_dir(&templates).unwrap();
+        fs::write(
+            templates.join("main.rs.tmpl"),
+            "// day {{day}} of {{year}}\n",
+        )
+        .unwrap();
+
+        let dir = new_day(&root, Some(&templates), 2024, 12).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("src/main.rs")).unwrap(),
+            "// day 12 of 2024\n"
+        );
+        assert!(fs::read_to_string(dir.join("Cargo.toml"))
+            .unwrap()
+            .contains("d12"));
+        assert_eq!(registered(&root).unwrap(), ["d1", "d7", "d12"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
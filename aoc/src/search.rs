@@ -0,0 +1,404 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::grid::Grid;
+
+/* Graph given by the successors of a node along with the cost of
+the edge leading to each of them, nodes are only built when reached */
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    fn neighbours(&self, n: &Self::Node) -> Vec<(Self::Node, u64)>;
+}
+
+/* Graph made of a closure returning the successors of a node */
+pub struct FnGraph<N, F> {
+    f: F,
+    n: PhantomData<N>,
+}
+
+pub fn from_fn<N, F>(f: F) -> FnGraph<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: Fn(&N) -> Vec<(N, u64)>,
+{
+    FnGraph { f, n: PhantomData }
+}
+
+impl<N, F> Graph for FnGraph<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: Fn(&N) -> Vec<(N, u64)>,
+{
+    type Node = N;
+
+    fn neighbours(&self, n: &N) -> Vec<(N, u64)> {
+        (self.f)(n)
+    }
+}
+
+/* Grid cells linked to their 4 neighbours, cost gets the values
+of both cells and returns None when the step is not allowed */
+pub struct GridGraph<'a, T, F> {
+    g: &'a Grid<T>,
+    cost: F,
+}
+
+impl<T> Grid<T> {
+    pub fn graph<F>(&self, cost: F) -> GridGraph<'_, T, F>
+    where
+        F: Fn(&T, &T) -> Option<u64>,
+    {
+        GridGraph { g: self, cost }
+    }
+}
+
+impl<T, F> Graph for GridGraph<'_, T, F>
+where
+    F: Fn(&T, &T) -> Option<u64>,
+{
+    type Node = (usize, usize);
+
+    fn neighbours(&self, n: &(usize, usize)) -> Vec<((usize, usize), u64)> {
+        self.g
+            .neighbours4(*n)
+            .filter_map(|p| (self.cost)(&self.g[*n], &self.g[p]).map(|c| (p, c)))
+            .collect()
+    }
+}
+
+/* Cost of every reached node and the node it was reached from */
+#[derive(Debug, Clone)]
+pub struct Paths<N> {
+    dist: HashMap<N, u64>,
+    prev: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash> Paths<N> {
+    fn new(start: N) -> Paths<N> {
+        Paths {
+            dist: HashMap::from([(start, 0)]),
+            prev: HashMap::new(),
+        }
+    }
+
+    pub fn cost(&self, n: &N) -> Option<u64> {
+        self.dist.get(n).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dist.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dist.is_empty()
+    }
+
+    pub fn reached(&self) -> impl Iterator<Item = (&N, u64)> {
+        self.dist.iter().map(|(n, c)| (n, *c))
+    }
+
+    /* Most expensive node to reach */
+    pub fn farthest(&self) -> Option<(&N, u64)> {
+        self.reached().max_by_key(|(_, c)| *c)
+    }
+
+    /* Nodes from the start to n, both included */
+    pub fn path_to(&self, n: &N) -> Option<Vec<N>> {
+        if !self.dist.contains_key(n) {
+            return None;
+        }
+
+        let mut ret = vec![n.clone()];
+        while let Some(p) = self.prev.get(ret.last().unwrap()) {
+            ret.push(p.clone());
+        }
+
+        ret.reverse();
+        Some(ret)
+    }
+}
+
+/* Breadth first, each edge counts as 1 whatever its cost */
+pub fn bfs<G: Graph>(g: &G, start: G::Node) -> Paths<G::Node> {
+    let mut paths = Paths::new(start.clone());
+    let mut queue: VecDeque<(G::Node, u64)> = VecDeque::from([(start, 0)]);
+
+    while let Some((n, d)) = queue.pop_front() {
+        for (next, _) in g.neighbours(&n) {
+            if paths.dist.contains_key(&next) {
+                continue;
+            }
+
+            paths.dist.insert(next.clone(), d + 1);
+            paths.prev.insert(next.clone(), n.clone());
+            queue.push_back((next, d + 1));
+        }
+    }
+
+    paths
+}
+
+/* Best first search, h estimates the cost left to a goal. Nodes are
+queued again when a cheaper way is found so an estimate which never
+goes over the real cost is enough to get the cheapest path */
+fn best_first<G, F, H>(g: &G, start: G::Node, is_goal: F, h: H) -> (Paths<G::Node>, Option<G::Node>)
+where
+    G: Graph,
+    F: Fn(&G::Node) -> bool,
+    H: Fn(&G::Node) -> u64,
+{
+    let mut paths = Paths::new(start.clone());
+
+    /* The heap holds (estimate, cost, index in pending) */
+    let mut heap: BinaryHeap<Reverse<(u64, u64, usize)>> = BinaryHeap::new();
+    heap.push(Reverse((h(&start), 0, 0)));
+    let mut pending: Vec<G::Node> = vec![start];
+
+    while let Some(Reverse((_, cost, i))) = heap.pop() {
+        let n = pending[i].clone();
+
+        /* A cheaper way was found since it was queued */
+        if cost > paths.dist[&n] {
+            continue;
+        }
+
+        if is_goal(&n) {
+            return (paths, Some(n));
+        }
+
+        for (next, c) in g.neighbours(&n) {
+            let nc = cost + c;
+
+            if paths.dist.get(&next).is_some_and(|d| *d <= nc) {
+                continue;
+            }
+
+            paths.dist.insert(next.clone(), nc);
+            paths.prev.insert(next.clone(), n.clone());
+            heap.push(Reverse((nc + h(&next), nc, pending.len())));
+            pending.push(next);
+        }
+    }
+
+    (paths, None)
+}
+
+/* Cheapest cost of every node reachable from start */
+pub fn dijkstra<G: Graph>(g: &G, start: G::Node) -> Paths<G::Node> {
+    best_first(g, start, |_| false, |_| 0).0
+}
+
+/* Cost and nodes of the cheapest path to a goal, h must never
+overestimate the cost left (0 everywhere is plain Dijkstra) */
+pub fn astar<G, F, H>(g: &G, start: G::Node, is_goal: F, h: H) -> Option<(u64, Vec<G::Node>)>
+where
+    G: Graph,
+    F: Fn(&G::Node) -> bool,
+    H: Fn(&G::Node) -> u64,
+{
+    let (paths, goal) = best_first(g, start, is_goal, h);
+    let goal = goal?;
+
+    Some((paths.cost(&goal)?, paths.path_to(&goal)?))
+}
+
+/* Nodes already reached, for callers who have a cheaper set than a HashSet */
+pub trait Visited<N> {
+    /* Returns false if n was already in */
+    fn insert(&mut self, n: &N) -> bool;
+}
+
+impl<N: Clone + Eq + Hash> Visited<N> for HashSet<N> {
+    fn insert(&mut self, n: &N) -> bool {
+        HashSet::insert(self, n.clone())
+    }
+}
+
+/* Every node reachable from start in breadth first order */
+pub fn flood_fill<G: Graph>(g: &G, start: G::Node) -> Vec<G::Node> {
+    flood_fill_with(g, start, &mut HashSet::new())
+}
+
+/* flood_fill marking the nodes it reaches in seen, the nodes
+already in are not entered, nothing at all if start is one */
+pub fn flood_fill_with<G, V>(g: &G, start: G::Node, seen: &mut V) -> Vec<G::Node>
+where
+    G: Graph,
+    V: Visited<G::Node>,
+{
+    if !seen.insert(&start) {
+        return Vec::new();
+    }
+
+    let mut ret = vec![start];
+    let mut x = 0;
+
+    while x < ret.len() {
+        for (next, _) in g.neighbours(&ret[x]) {
+            if seen.insert(&next) {
+                ret.push(next);
+            }
+        }
+        x += 1;
+    }
+
+    ret
+}
+
+/* Groups of nodes reachable from each other, for graphs where each
+edge goes both ways. Nodes not listed are only found through others */
+pub fn components<G, I>(g: &G, nodes: I) -> Vec<Vec<G::Node>>
+where
+    G: Graph,
+    I: IntoIterator<Item = G::Node>,
+{
+    let mut seen: HashSet<G::Node> = HashSet::new();
+    let mut ret: Vec<Vec<G::Node>> = Vec::new();
+
+    for n in nodes {
+        let comp = flood_fill_with(g, n, &mut seen);
+
+        if !comp.is_empty() {
+            ret.push(comp);
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /* 0 -4-> 1 -1-> 3 -3-> 4, 0 -1-> 2 -2-> 1, 2 -5-> 3, 5 -1-> 0 */
+    fn weighted() -> impl Graph<Node = u32> {
+        from_fn(|n: &u32| match n {
+            0 => vec![(1, 4), (2, 1)],
+            1 => vec![(3, 1)],
+            2 => vec![(1, 2), (3, 5)],
+            3 => vec![(4, 3)],
+            5 => vec![(0, 1)],
+            _ => vec![],
+        })
+    }
+
+    /* 0 - 1 - 2, 3 - 4 and 5 alone, every edge both ways */
+    fn undirected() -> impl Graph<Node = u32> {
+        from_fn(|n: &u32| {
+            let edges = [(0, 1), (1, 2), (3, 4)];
+            edges
+                .iter()
+                .filter_map(|(a, b)| {
+                    if n == a {
+                        Some((*b, 1))
+                    } else if n == b {
+                        Some((*a, 1))
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn bfs_counts_edges() {
+        let p = bfs(&weighted(), 0);
+
+        assert_eq!(p.len(), 5);
+        assert_eq!(p.cost(&3), Some(2));
+        assert_eq!(p.cost(&4), Some(3));
+        assert_eq!(p.cost(&5), None);
+        assert_eq!(p.path_to(&4), Some(vec![0, 1, 3, 4]));
+        assert_eq!(p.path_to(&0), Some(vec![0]));
+        assert_eq!(p.path_to(&5), None);
+        assert_eq!(p.farthest(), Some((&4, 3)));
+    }
+
+    #[test]
+    fn dijkstra_cheapest() {
+        let p = dijkstra(&weighted(), 0);
+
+        assert_eq!(p.cost(&1), Some(3));
+        assert_eq!(p.cost(&3), Some(4));
+        assert_eq!(p.cost(&4), Some(7));
+        assert_eq!(p.path_to(&4), Some(vec![0, 2, 1, 3, 4]));
+        assert_eq!(p.cost(&5), None);
+    }
+
+    #[test]
+    fn astar_hand_built() {
+        let g = weighted();
+
+        assert_eq!(
+            astar(&g, 0, |n| *n == 4, |_| 0),
+            Some((7, vec![0, 2, 1, 3, 4]))
+        );
+        assert_eq!(astar(&g, 0, |n| *n == 5, |_| 0), None);
+        assert_eq!(astar(&g, 4, |n| *n == 4, |_| 0), Some((0, vec![4])));
+    }
+
+    /* Entering a cell costs its digit, at least 1, so the manhattan
+    distance to the goal never overestimates */
+    #[test]
+    fn astar_manhattan() {
+        let mut rng = Rng::new(43);
+
+        for _ in 0..50 {
+            let (rows, cols) = (1 + rng.index(8), 1 + rng.index(8));
+            let mut grid = Grid::new(rows, cols, 0u64);
+            for v in grid.iter_mut() {
+                /* 0 is a wall */
+                *v = rng.below(10);
+            }
+
+            let start = (0, 0);
+            let goal = (rows - 1, cols - 1);
+            grid[start] = 1;
+            grid[goal] = 1;
+
+            let g = grid.graph(|_, b| (*b != 0).then_some(*b));
+            let h = |p: &(usize, usize)| (goal.0 - p.0 + goal.1 - p.1) as u64;
+
+            let want = dijkstra(&g, start).cost(&goal);
+            let got = astar(&g, start, |p| *p == goal, h);
+
+            assert_eq!(got.as_ref().map(|v| v.0), want);
+
+            if let Some((cost, path)) = got {
+                assert_eq!(path.first(), Some(&start));
+                assert_eq!(path.last(), Some(&goal));
+                assert_eq!(path[1..].iter().map(|p| grid[*p]).sum::<u64>(), cost);
+            }
+        }
+    }
+
+    #[test]
+    fn flood_fill_with_seen() {
+        let g = weighted();
+
+        let mut seen: HashSet<u32> = HashSet::from([1]);
+        assert_eq!(flood_fill_with(&g, 0, &mut seen), vec![0, 2, 3, 4]);
+        assert_eq!(seen.len(), 5);
+
+        /* Nothing from a node already seen */
+        assert!(flood_fill_with(&g, 2, &mut seen).is_empty());
+
+        assert_eq!(flood_fill(&g, 5), vec![5, 0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn components_once_each() {
+        let comps = components(&undirected(), 0..6);
+
+        assert_eq!(comps, vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+
+        /* Nodes given twice or found through others start nothing */
+        let comps = components(&undirected(), [2, 0, 4, 3, 2]);
+        assert_eq!(comps, vec![vec![2, 1, 0], vec![4, 3]]);
+    }
+}
@@ -0,0 +1,34 @@
+use std::fs;
+
+use crate::parse::ParseError;
+
+/* A day's puzzle, parsed once from its input then asked for each part,
+None being a part that is not solved yet */
+pub trait Solution: Sized {
+    fn parse(input: &str) -> Result<Self, ParseError>;
+
+    fn part1(&self) -> Option<String>;
+
+    fn part2(&self) -> Option<String>;
+}
+
+/* Solves data.txt of the current directory and prints
+the answers, checked against the ledger */
+pub fn run<S: Solution>() {
+    let data = fs::read_to_string("data.txt").unwrap_or_else(|e| {
+        eprintln!("Cannot read data.txt: {}", e);
+        std::process::exit(1);
+    });
+
+    let s = S::parse(&data).unwrap_or_else(|e| {
+        eprintln!("data.txt {}", e);
+        std::process::exit(1);
+    });
+
+    for (x, v) in [s.part1(), s.part2()].into_iter().enumerate() {
+        match v {
+            Some(v) => crate::ledger::answer(x as u8 + 1, v),
+            None => println!("Part {} : not solved yet", x + 1),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+
+use crate::http;
+
+/* Local stand-in for the site, serving canned inputs from
+dir/YEAR/dayN.txt and judging submissions against the answer
+in dir/YEAR/dayN.partP.txt so the client can be tried without
+a real session. Like the site it refuses answers to a part
+once it was answered right */
+pub struct Stub {
+    dir: PathBuf,
+    /* "YEAR/DAY/PART" answered right */
+    solved: RefCell<HashSet<String>>,
+}
+
+struct Received {
+    method: String,
+    path: String,
+    session: Option<String>,
+    body: String,
+}
+
+fn receive(s: &TcpStream) -> io::Result<Received> {
+    let mut r = BufReader::new(s);
+    let mut line = String::new();
+    r.read_line(&mut line)?;
+
+    let mut words = line.split_whitespace();
+    let method = words.next().unwrap_or_default().to_string();
+    let path = words.next().unwrap_or_default().to_string();
+
+    let mut session = None;
+    let mut len = 0;
+
+    loop {
+        line.clear();
+        if r.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+
+        let (k, v) = match line.split_once(':') {
+            Some((k, v)) => (k.trim().to_lowercase(), v.trim()),
+            None => continue,
+        };
+
+        match k.as_str() {
+            "cookie" => session = v.strip_prefix("session=").map(str::to_string),
+            "content-length" => len = v.parse().unwrap_or(0),
+            _ => (),
+        }
+    }
+
+    let mut body = vec![0; len];
+    r.read_exact(&mut body)?;
+
+    Ok(Received {
+        method,
+        path,
+        session,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+/* What the site says to another answer to a solved part */
+const SOLVED: &str = "You don't seem to be solving the right level.  Did you already complete it?";
+
+fn page(msg: &str) -> String {
+    format!(
+        "<html><body><main><article><p>{}</p></article></main></body></html>\n",
+        msg
+    )
+}
+
+/* Page with the site's wording for a wrong answer */
+fn judge(expected: &str, answer: &str) -> String {
+    let hint = match (answer.parse::<i128>(), expected.parse::<i128>()) {
+        (Ok(a), Ok(e)) if a > e => " your answer is too high.",
+        (Ok(a), Ok(e)) if a < e => " your answer is too low.",
+        _ => "",
+    };
+
+    page(&format!("That's not the right answer;{}", hint))
+}
+
+impl Stub {
+    pub fn new(dir: &Path) -> Stub {
+        Stub {
+            dir: dir.to_path_buf(),
+            solved: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /* Answers one request at a time until the listener fails,
+    a connection going wrong is logged and the next one served */
+    pub fn serve(&self, l: TcpListener) -> io::Result<()> {
+        for s in l.incoming() {
+            if let Err(e) = self.connection(s?) {
+                eprintln!("Connection failed: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn connection(&self, mut s: TcpStream) -> io::Result<()> {
+        let req = receive(&s)?;
+        let (status, body) = self.answer(&req);
+
+        println!("{} {} {}", req.method, req.path, status);
+
+        write!(
+            s,
+            "HTTP/1.0 {} Stub\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    fn answer(&self, req: &Received) -> (u16, String) {
+        if req.session.as_deref().unwrap_or("").is_empty() {
+            return (
+                400,
+                "Puzzle inputs differ by user. Please log in.\n".to_string(),
+            );
+        }
+
+        let parts: Vec<&str> = req.path.trim_matches('/').split('/').collect();
+
+        match (req.method.as_str(), &parts[..]) {
+            ("GET", [year, "day", day, "input"]) => {
+                let path = self.dir.join(year).join(format!("day{}.txt", day));
+
+                match fs::read_to_string(path) {
+                    Ok(v) => (200, v),
+                    Err(_) => (404, "404 Not Found\n".to_string()),
+                }
+            }
+            ("POST", [year, "day", day, "answer"]) => {
+                let form = http::form_decode(&req.body);
+                let field = |k: &str| {
+                    form.iter()
+                        .find(|v| v.0 == k)
+                        .map(|v| v.1.clone())
+                        .unwrap_or_default()
+                };
+
+                let path =
+                    self.dir
+                        .join(year)
+                        .join(format!("day{}.part{}.txt", day, field("level")));
+
+                let key = format!("{}/{}/{}", year, day, field("level"));
+
+                if self.solved.borrow().contains(&key) {
+                    return (200, page(SOLVED));
+                }
+
+                match fs::read_to_string(path) {
+                    Ok(v) if v.trim() == field("answer") => {
+                        self.solved.borrow_mut().insert(key);
+                        (200, page("That's the right answer!"))
+                    }
+                    Ok(v) => (200, judge(v.trim(), &field("answer"))),
+                    Err(_) => (404, "404 Not Found\n".to_string()),
+                }
+            }
+            _ => (404, "404 Not Found\n".to_string()),
+        }
+    }
+}
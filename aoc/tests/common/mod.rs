@@ -0,0 +1,55 @@
+use std::cell::Cell;
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use aoc::client::Config;
+use aoc::http::{Http, HttpError, Request, Response};
+use aoc::stub::Stub;
+
+/* Counts what reaches the backend to tell cache hits apart */
+pub struct Counting<'a, H: Http> {
+    pub http: H,
+    pub sent: &'a Cell<usize>,
+}
+
+impl<H: Http> Http for Counting<'_, H> {
+    fn send(&self, req: &Request) -> Result<Response, HttpError> {
+        self.sent.set(self.sent.get() + 1);
+        self.http.send(req)
+    }
+}
+
+/* Fresh directory with the site's files in site/ and the cache in cache/ */
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    fs::create_dir_all(dir.join("site/2023")).unwrap();
+    fs::write(dir.join("site/2023/day1.txt"), "1abc2\npqr3stu8vwx\n").unwrap();
+    fs::write(dir.join("site/2023/day1.part1.txt"), "142\n").unwrap();
+
+    dir
+}
+
+/* Stub on a free port of its own, the url to reach it */
+pub fn serve(dir: &Path) -> String {
+    let l = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", l.local_addr().unwrap());
+    let stub = Stub::new(&dir.join("site"));
+
+    thread::spawn(move || stub.serve(l));
+
+    url
+}
+
+pub fn config(dir: &Path, url: String) -> Config {
+    Config {
+        url,
+        session: Some("53616c7465645f5f".to_string()),
+        cache: dir.join("cache"),
+        interval: Duration::ZERO,
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use std::cell::Cell;
+use std::fs;
+use std::io::Write;
+use std::net::TcpStream;
+use std::process::Command;
+
+use aoc::client::{Client, ClientError};
+use aoc::http::{Curl, Http, Plain, Request};
+
+use common::{config, scratch, serve, Counting};
+
+fn fetch_cached<H: Http>(name: &str, http: H) {
+    let dir = scratch(name);
+    let url = serve(&dir);
+
+    let sent = Cell::new(0);
+    let client = Client::new(Counting { http, sent: &sent }, config(&dir, url));
+
+    assert_eq!(client.input(2023, 1).unwrap(), "1abc2\npqr3stu8vwx\n");
+    assert_eq!(
+        fs::read_to_string(client.input_path(2023, 1)).unwrap(),
+        "1abc2\npqr3stu8vwx\n"
+    );
+
+    /* Served from the cache even once the site lost it */
+    fs::remove_file(dir.join("site/2023/day1.txt")).unwrap();
+    assert_eq!(client.input(2023, 1).unwrap(), "1abc2\npqr3stu8vwx\n");
+    assert_eq!(sent.get(), 1);
+
+    match client.input(2023, 2) {
+        Err(ClientError::Status(404, _)) => (),
+        v => panic!("expected a 404, got {:?}", v),
+    }
+    assert!(!client.input_path(2023, 2).exists());
+    assert_eq!(sent.get(), 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn plain_fetch() {
+    fetch_cached("plain", Plain);
+}
+
+#[test]
+fn plain_without_session() {
+    let dir = scratch("nosession");
+    let url = serve(&dir);
+
+    let res = Plain
+        .send(&Request {
+            url: format!("{}/2023/day/1/input", url),
+            session: String::new(),
+            form: None,
+        })
+        .unwrap();
+
+    assert_eq!(res.status, 400);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/* A client hanging up early only costs its own connection */
+#[test]
+fn stub_survives_bad_connection() {
+    let dir = scratch("badconn");
+    let url = serve(&dir);
+
+    {
+        let mut s = TcpStream::connect(url.strip_prefix("http://").unwrap()).unwrap();
+        write!(
+            s,
+            "POST /2023/day/1/answer HTTP/1.0\r\nContent-Length: 100\r\n\r\nlevel=1"
+        )
+        .unwrap();
+    }
+
+    let res = Plain
+        .send(&Request {
+            url: format!("{}/2023/day/1/input", url),
+            session: "53616c7465645f5f".to_string(),
+            form: None,
+        })
+        .unwrap();
+
+    assert_eq!(res.status, 200);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/* Same through curl, which gets the session on stdin */
+#[test]
+fn curl_fetch() {
+    if Command::new("curl").arg("--version").output().is_err() {
+        eprintln!("No curl, skipped");
+        return;
+    }
+
+    fetch_cached("curl", Curl);
+}
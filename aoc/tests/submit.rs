@@ -0,0 +1,145 @@
+mod common;
+
+use std::cell::Cell;
+use std::fs;
+
+use aoc::client::{Client, ClientError, Submission};
+use aoc::http::Plain;
+use aoc::ledger::{Ledger, Verdict};
+
+use common::{config, scratch, serve, Counting};
+
+#[test]
+fn correct() {
+    let dir = scratch("correct");
+    let sent = Cell::new(0);
+    let client = Client::new(
+        Counting {
+            http: Plain,
+            sent: &sent,
+        },
+        config(&dir, serve(&dir)),
+    );
+    let mut ledger = Ledger::open(dir.join("answers.txt")).unwrap();
+
+    let v = client.submit_once(&mut ledger, 2023, 1, 1, "142").unwrap();
+    assert_eq!(v, Submission::Sent(Verdict::Correct));
+
+    /* Known from then on, even to a ledger read back from the file */
+    let mut ledger = Ledger::open(dir.join("answers.txt")).unwrap();
+    assert_eq!(ledger.correct(1).map(|e| e.answer.as_str()), Some("142"));
+
+    let v = client.submit_once(&mut ledger, 2023, 1, 1, "142").unwrap();
+    assert_eq!(v, Submission::Known(Verdict::Correct));
+    assert_eq!(sent.get(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn too_high_too_low() {
+    let dir = scratch("highlow");
+    let sent = Cell::new(0);
+    let client = Client::new(
+        Counting {
+            http: Plain,
+            sent: &sent,
+        },
+        config(&dir, serve(&dir)),
+    );
+    let mut ledger = Ledger::open(dir.join("answers.txt")).unwrap();
+
+    for (answer, verdict) in [
+        ("200", Verdict::TooHigh),
+        ("100", Verdict::TooLow),
+        ("abc", Verdict::Wrong),
+    ] {
+        let v = client.submit_once(&mut ledger, 2023, 1, 1, answer).unwrap();
+        assert_eq!(v, Submission::Sent(verdict), "{}", answer);
+    }
+
+    let ledger = Ledger::open(dir.join("answers.txt")).unwrap();
+    let kept: Vec<(u8, &str, Verdict)> = ledger
+        .entries()
+        .iter()
+        .map(|e| (e.part, e.answer.as_str(), e.verdict))
+        .collect();
+
+    assert_eq!(
+        kept,
+        [
+            (1, "200", Verdict::TooHigh),
+            (1, "100", Verdict::TooLow),
+            (1, "abc", Verdict::Wrong)
+        ]
+    );
+    assert_eq!(sent.get(), 3);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/* The site refuses any answer to a solved part, which is not a verdict */
+#[test]
+fn already_submitted() {
+    let dir = scratch("already");
+    let client = Client::new(Plain, config(&dir, serve(&dir)));
+
+    let mut ledger = Ledger::open(dir.join("answers.txt")).unwrap();
+    let v = client.submit_once(&mut ledger, 2023, 1, 1, "142").unwrap();
+    assert_eq!(v, Submission::Sent(Verdict::Correct));
+
+    /* Solved from elsewhere, this ledger does not know */
+    let mut other = Ledger::open(dir.join("other.txt")).unwrap();
+
+    match client.submit_once(&mut other, 2023, 1, 1, "7") {
+        Err(ClientError::Refused(m)) => {
+            assert!(m.contains("Did you already complete it?"), "{}", m)
+        }
+        v => panic!("expected a refusal, got {:?}", v),
+    }
+    assert!(other.entries().is_empty());
+    assert!(!dir.join("other.txt").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/* Nothing is listening, any request would fail */
+#[test]
+fn known_wrong_stays_local() {
+    let dir = scratch("local");
+    let sent = Cell::new(0);
+    let client = Client::new(
+        Counting {
+            http: Plain,
+            sent: &sent,
+        },
+        config(&dir, "http://127.0.0.1:1".to_string()),
+    );
+
+    fs::write(dir.join("answers.txt"), "1 too-high 1700000000 200\n1 too-low 1700000000 100\n2 correct 1700000000 281\n1 wrong 1700000000 150\n").unwrap();
+    let mut ledger = Ledger::open(dir.join("answers.txt")).unwrap();
+
+    for (part, answer, verdict) in [
+        (1, "150", Verdict::Wrong),
+        (1, "200", Verdict::TooHigh),
+        (1, "250", Verdict::TooHigh),
+        (1, "100", Verdict::TooLow),
+        (1, "7", Verdict::TooLow),
+        (2, "281", Verdict::Correct),
+        (2, "282", Verdict::Wrong),
+    ] {
+        let v = client
+            .submit_once(&mut ledger, 2023, 1, part, answer)
+            .unwrap();
+        assert_eq!(v, Submission::Known(verdict), "part {} {}", part, answer);
+    }
+
+    assert_eq!(sent.get(), 0);
+    assert_eq!(ledger.entries().len(), 4);
+
+    /* Only an unknown answer goes out */
+    assert!(client.submit_once(&mut ledger, 2023, 1, 1, "142").is_err());
+    assert_eq!(sent.get(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,59 +1,183 @@
 use std::error::Error;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::fs;
+
+const ENGLISH: &str = "one 1\ntwo 2\nthree 3\nfour 4\nfive 5\nsix 6\nseven 7\neight 8\nnine 9";
+const FRENCH: &str = "un 1\ndeux 2\ntrois 3\nquatre 4\ncinq 5\nsix 6\nsept 7\nhuit 8\nneuf 9";
+const GERMAN: &str = "eins 1\nzwei 2\ndrei 3\nvier 4\nfünf 5\nsechs 6\nsieben 7\nacht 8\nneun 9";
+
+/* Tokens standing for a number, a multi-digit token counts
+as its first digit at the start and its last one at the end.
+Longest tokens come first so "seventeen" wins over "seven" */
+#[derive(Debug, Clone)]
+struct Dictionary {
+    tokens: Vec<(Vec<u8>, (u32, u32))>,
+    ignore_case: bool,
+}
+
+impl Dictionary {
+    /* One "token value" pair per line, '#' starts a comment */
+    fn parse(src: &str, ignore_case: bool) -> Result<Dictionary, String> {
+        let mut tokens = Vec::new();
+
+        for (x, l) in src.lines().enumerate() {
+            let l = l.trim();
+
+            if l.is_empty() || l.starts_with('#') {
+                continue;
+            }
+
+            let (tok, val) = l
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("line {}: expected 'token value'", x + 1))?;
+
+            let val = val.trim();
+            if val.is_empty() || !val.bytes().all(|v| v.is_ascii_digit()) {
+                return Err(format!("line {}: bad value '{}'", x + 1, val));
+            }
+
+            let digits = val.as_bytes();
+            let first = (digits[0] - b'0') as u32;
+            let last = (digits[digits.len() - 1] - b'0') as u32;
+
+            let tok = if ignore_case {
+                tok.to_lowercase()
+            } else {
+                tok.to_string()
+            };
+
+            tokens.push((tok.into_bytes(), (first, last)));
+        }
+
+        tokens.sort_by_key(|t| std::cmp::Reverse(t.0.len()));
+
+        Ok(Dictionary {
+            tokens,
+            ignore_case,
+        })
+    }
+
+    fn builtin(name: &str, ignore_case: bool) -> Option<Dictionary> {
+        let src = match name {
+            "en" => ENGLISH,
+            "fr" => FRENCH,
+            "de" => GERMAN,
+            _ => return None,
+        };
+
+        Some(Dictionary::parse(src, ignore_case).unwrap())
+    }
+
+    fn load(path: &str, ignore_case: bool) -> Result<Dictionary, String> {
+        let src = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Dictionary::parse(&src, ignore_case).map_err(|e| format!("{} {}", path, e))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Mode<'a> {
+    /* Part 1, only '0'..='9' count */
+    Digits,
+    /* Part 2, dictionary tokens count as well */
+    Words(&'a Dictionary),
+}
+
+/* First and last digit of the number starting at offset i, tokens
+may overlap ("eightwo") as each offset is checked on its own */
+fn digit_at(line: &[u8], i: usize, mode: Mode) -> Option<(u32, u32)> {
+    if line[i].is_ascii_digit() {
+        let v = (line[i] - b'0') as u32;
+        return Some((v, v));
+    }
+
+    if let Mode::Words(dict) = mode {
+        for (w, v) in dict.tokens.iter() {
+            if line[i..].starts_with(w) {
+                return Some(*v);
+            }
+        }
+    }
+
+    None
+}
+
+/* First digit scanning from the start, last one scanning from the end */
+fn calibration(line: &str, mode: Mode) -> Option<u32> {
+    let lower;
+    let l = match mode {
+        Mode::Words(d) if d.ignore_case => {
+            lower = line.to_lowercase();
+            lower.as_bytes()
+        }
+        _ => line.as_bytes(),
+    };
+
+    let first = (0..l.len()).find_map(|i| digit_at(l, i, mode))?.0;
+    let last = (0..l.len()).rev().find_map(|i| digit_at(l, i, mode))?.1;
+
+    Some(first * 10 + last)
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let file = File::open("./data.txt")?;
-    let mut buf_reader = BufReader::new(file);
-    let mut contents = String::new();
-    buf_reader.read_to_string(&mut contents)?;
-
-    let mut total = 0;
-
-    for line in contents.split('\n') {
-        let replacee = vec![
-            ("one", "1"),
-            ("two", "2"),
-            ("three", "3"),
-            ("four", "4"),
-            ("five", "5"),
-            ("six", "6"),
-            ("seven", "7"),
-            ("eight", "8"),
-            ("nine", "9"),
-        ];
-
-        let mut sline = line.to_string();
-
-        for rep in replacee.iter() {
-            sline = sline.replace(rep.0, format!("{}{}{}", rep.0, rep.1, rep.0).as_str());
-            println!("{}", sline);
+    /* d1 [--dict en|fr|de|FILE] [--ignore-case] */
+    let mut dict_name = "en".to_string();
+    let mut ignore_case = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--dict" => dict_name = args.next().ok_or("--dict needs a name or a file")?,
+            "--ignore-case" => ignore_case = true,
+            _ => return Err(format!("Unknown argument {}", a).into()),
         }
+    }
 
-        let values: Vec<i32> = sline
-            .as_bytes()
-            .iter()
-            .filter(|v| (**v <= b'9') & (b'0' <= **v))
-            .filter_map(|v| {
-                if let Ok(s) = String::from_utf8(vec![*v]) {
-                    if let Ok(v) = s.parse::<i32>() {
-                        return Some(v);
-                    }
-                }
-                None
-            })
-            .collect();
-
-        if !values.is_empty() {
-            println!("{:?}", values);
-            let values: i32 = values[0] * 10 + values[values.len() - 1];
-            println!("FL {}", values);
-
-            total += values;
+    let dict = match Dictionary::builtin(&dict_name, ignore_case) {
+        Some(d) => d,
+        None => Dictionary::load(&dict_name, ignore_case)?,
+    };
+
+    let mut r = aoc::input::open("./data.txt")?;
+
+    let mut totals = [0u64; 2];
+
+    while let Some(line) = r.next_line()? {
+        for (x, mode) in [Mode::Digits, Mode::Words(&dict)].iter().enumerate() {
+            totals[x] += calibration(line, *mode).unwrap_or(0) as u64;
         }
     }
 
-    println!("Total: {total}");
+    for (x, total) in totals.iter().enumerate() {
+        aoc::ledger::answer(x as u8 + 1, total);
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_token() {
+        /* Shorter token first in the file on purpose */
+        let dict = Dictionary::parse("seven 7\nseventeen 17\nten 10", false).unwrap();
+        let mode = Mode::Words(&dict);
+
+        assert_eq!(calibration("seventeen", mode), Some(17));
+        assert_eq!(calibration("sevenx", mode), Some(77));
+        assert_eq!(calibration("seventeenten", mode), Some(10));
+        assert_eq!(calibration("3seventeen", mode), Some(37));
+    }
+
+    #[test]
+    fn english() {
+        let dict = Dictionary::builtin("en", false).unwrap();
+        let mode = Mode::Words(&dict);
+
+        assert_eq!(calibration("two1nine", mode), Some(29));
+        assert_eq!(calibration("eightwothree", mode), Some(83));
+        assert_eq!(calibration("zoneight234", mode), Some(14));
+        assert_eq!(calibration("treb7uchet", Mode::Digits), Some(77));
+        assert_eq!(calibration("trebuchet", Mode::Digits), None);
+    }
+}
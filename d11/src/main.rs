@@ -1,12 +1,11 @@
 use std::fs::File;
 use std::io::{BufReader, Read};
 
+use aoc::grid::Grid;
+
 #[derive(Debug)]
 struct Entry {
-    lines: Vec<Vec<u8>>,
-    collumns: Vec<Vec<u8>>,
-    w: usize,
-    h: usize,
+    g: Grid<u8>,
 }
 
 fn abs(v: i64) -> i64 {
@@ -21,27 +20,24 @@ impl Entry {
         v.len() == v.iter().filter(|v| **v == b'.').count()
     }
 
-    fn empty_offsets(v: &[Vec<u8>]) -> Vec<usize> {
-        v.iter()
+    fn empty_offsets(g: &Grid<u8>) -> Vec<usize> {
+        g.rows_iter()
             .enumerate()
             .filter_map(|(i, v)| if Entry::is_empty(v) { Some(i) } else { None })
             .collect()
     }
 
     fn galaxies(&self, expand_factor: usize) -> Vec<(usize, usize)> {
-        let mut ret: Vec<(usize, usize)> = Vec::new();
-
-        for (x, l) in self.lines.iter().enumerate() {
-            for (y, v) in l.iter().enumerate() {
-                if *v == b'#' {
-                    ret.push((x, y));
-                }
-            }
-        }
+        let mut ret: Vec<(usize, usize)> = self
+            .g
+            .cells()
+            .filter(|(_, v)| **v == b'#')
+            .map(|(p, _)| p)
+            .collect();
 
         /* We have the list of galaxies */
-        let list_of_empty_lines: Vec<usize> = Entry::empty_offsets(&self.lines);
-        let list_of_empty_cols: Vec<usize> = Entry::empty_offsets(&self.collumns);
+        let list_of_empty_lines: Vec<usize> = Entry::empty_offsets(&self.g);
+        let list_of_empty_cols: Vec<usize> = Entry::empty_offsets(&self.g.transpose().to_grid());
 
         for g in ret.iter_mut() {
             let lower_x: usize = list_of_empty_lines.iter().filter(|v| **v < g.0).count();
@@ -54,20 +50,6 @@ impl Entry {
         ret
     }
 
-    fn rotate(v: &[Vec<u8>]) -> Vec<Vec<u8>> {
-        let mut ret: Vec<Vec<u8>> = Vec::new();
-
-        let len = v[0].len();
-
-        /* Now we have loaded the lines we want to generate the collums */
-        for i in 0..len {
-            let cdata = v.iter().map(|vv| vv[i]).collect::<Vec<u8>>();
-            ret.push(cdata);
-        }
-
-        ret
-    }
-
     fn distance(a: &(usize, usize), b: &(usize, usize)) -> usize {
         (abs(b.0 as i64 - a.0 as i64) + abs(b.1 as i64 - a.1 as i64)) as usize
     }
@@ -86,27 +68,9 @@ impl Entry {
     }
 
     fn new(data: &str) -> Entry {
-        let mut ret = Entry {
-            lines: Vec::new(),
-            collumns: Vec::new(),
-            w: 0,
-            h: 0,
-        };
-
-        for l in data.split('\n') {
-            let ldata = l.trim().as_bytes().to_vec();
-            if ret.w == 0 {
-                ret.w = ldata.len();
-            } else {
-                assert!(ret.w == ldata.len());
-            }
-            ret.lines.push(ldata);
-            ret.h += 1;
+        Entry {
+            g: Grid::parse(data).unwrap(),
         }
-
-        ret.collumns = Entry::rotate(&ret.lines);
-
-        ret
     }
 }
 
@@ -119,9 +83,6 @@ fn main() {
 
     let e = Entry::new(&data);
 
-    println!(
-        "Part 1 {} Part 2 {}",
-        e.sum_of_distances(1),
-        e.sum_of_distances(1000000 - 1)
-    );
+    aoc::ledger::answer(1, e.sum_of_distances(1));
+    aoc::ledger::answer(2, e.sum_of_distances(1000000 - 1));
 }
@@ -0,0 +1,66 @@
+use aoc::diff::Differential;
+use aoc::rng::Rng;
+
+use crate::{gen, Puzzle};
+
+/* The memoized walk against counting every arrangement */
+pub(crate) struct Arrangements {
+    pub len: u64,
+    pub unknown: u64,
+}
+
+impl Differential for Arrangements {
+    type Input = Puzzle;
+    type Output = usize;
+
+    fn generate(&self, rng: &mut Rng) -> Puzzle {
+        let data = gen::springs(rng, 1, self.len, self.unknown);
+        Puzzle::new(data.trim_end()).unwrap()
+    }
+
+    fn shrink(&self, p: &Puzzle) -> Vec<Puzzle> {
+        let mut ret = Vec::new();
+
+        /* One spring less, or a known one */
+        for x in 0..p.d.len() {
+            if p.d.len() > 1 {
+                let mut s = p.clone();
+                s.d.remove(x);
+                ret.push(s);
+            }
+
+            if p.d[x] == b'?' {
+                for v in [b'.', b'#'] {
+                    let mut s = p.clone();
+                    s.d[x] = v;
+                    ret.push(s);
+                }
+            }
+        }
+
+        /* One group less, or a shorter one */
+        for x in 0..p.g.len() {
+            if p.g.len() > 1 {
+                let mut s = p.clone();
+                s.g.remove(x);
+                ret.push(s);
+            }
+
+            if p.g[x] > 1 {
+                let mut s = p.clone();
+                s.g[x] -= 1;
+                ret.push(s);
+            }
+        }
+
+        ret
+    }
+
+    fn fast(&self, p: &Puzzle) -> usize {
+        p.walk()
+    }
+
+    fn reference(&self, p: &Puzzle) -> usize {
+        p.count_brute()
+    }
+}
@@ -0,0 +1,47 @@
+use aoc::rng::Rng;
+
+/* `rows` rows of `len` springs, damaged ones come in runs of 1 to 5 and
+each spring is replaced by '?' with a probability of unknown percent */
+pub fn springs(rng: &mut Rng, rows: u64, len: u64, unknown: u64) -> String {
+    assert!(len > 0, "rows cannot be empty");
+
+    let len = len as usize;
+    let mut ret = String::new();
+
+    for _ in 0..rows {
+        let mut cells = vec![b'.'; len];
+        let mut x = rng.index(3);
+
+        while x < len {
+            let run = 1 + rng.index((len - x).min(5));
+            cells[x..x + run].fill(b'#');
+            x += run + 1 + rng.index(3);
+        }
+
+        /* Lines always list at least one group */
+        if !cells.contains(&b'#') {
+            let x = rng.index(len);
+            cells[x] = b'#';
+        }
+
+        let groups: Vec<String> = cells
+            .split(|v| *v == b'.')
+            .filter(|v| !v.is_empty())
+            .map(|v| v.len().to_string())
+            .collect();
+
+        for v in cells.iter_mut() {
+            if rng.chance(unknown) {
+                *v = b'?';
+            }
+        }
+
+        ret.push_str(&format!(
+            "{} {}\n",
+            String::from_utf8(cells).unwrap(),
+            groups.join(",")
+        ));
+    }
+
+    ret
+}
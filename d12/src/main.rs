@@ -1,33 +1,32 @@
-use rayon::prelude::*;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, Read};
-use std::str::{from_utf8, from_utf8_unchecked};
-use std::sync::Mutex;
+use std::fmt;
 
-#[derive(Debug)]
+use aoc::args::Args;
+use aoc::parse::{ParseError, Parser};
+use aoc::rng::Rng;
+
+mod diff;
+mod gen;
+
+#[derive(Debug, Clone)]
 struct Puzzle {
     d: Vec<u8>,
     g: Vec<i32>,
 }
 
 impl Puzzle {
-    fn new(data: &str) -> Puzzle {
-        let sp: Vec<&str> = data.split(' ').collect();
+    /* "???.### 1,1,3" */
+    fn new(data: &str) -> Result<Puzzle, ParseError> {
+        let mut p = Parser::new(data);
 
-        assert!(sp.len() == 2);
+        let d = p.token()?.as_bytes().to_vec();
+        let g = p.list(",", |v| v.number::<i32>())?;
 
-        Puzzle {
-            d: sp[0].as_bytes().to_vec(),
-            g: sp[1]
-                .split(',')
-                .map(|v| v.trim().parse::<i32>().unwrap())
-                .collect(),
-        }
+        Ok(Puzzle { d, g })
     }
 
     fn unfold(&mut self) {
-        let mut new_g: Vec<i32> = self
+        let new_g: Vec<i32> = self
             .g
             .iter()
             .cycle()
@@ -35,20 +34,17 @@ impl Puzzle {
             .cloned()
             .collect();
 
-        let prev = String::from_utf8(self.d.clone()).unwrap();
-        let d = format!("{}?{}?{}?{}?{}", prev, prev, prev, prev, prev);
-
-        self.d = d.as_bytes().to_vec();
+        self.d = [self.d.as_slice(); 5].join(&b'?');
         self.g = new_g;
     }
 
-    fn is_valid(&self, e: &str) -> bool {
+    fn is_valid(&self, e: &[u8]) -> bool {
         let v: Vec<i32> = Puzzle::group_vec(e);
         self.g == v
     }
 
-    fn group_vec(e: &str) -> Vec<i32> {
-        e.split('.')
+    fn group_vec(e: &[u8]) -> Vec<i32> {
+        e.split(|v| *v == b'.')
             .filter(|v| !v.is_empty())
             .map(|v| v.len() as i32)
             .collect()
@@ -56,22 +52,17 @@ impl Puzzle {
 
     fn _walk(
         &self,
-        d: &Vec<u8>,
+        d: &[u8],
         off: usize,
-        cache: &mut HashMap<(String, Vec<i32>), usize>,
+        cache: &mut HashMap<(Vec<u8>, Vec<i32>), usize>,
     ) -> usize {
         let mut ret = 0;
 
-        if !d.contains(&b'?') {
-            //let v = String::from_utf8(d.clone()).unwrap();
-            let valid = self.is_valid(from_utf8(d).unwrap());
-            if valid {
-                //println!("{} == {}", valid, v);
-                return 1;
-            }
+        if !d.contains(&b'?') && self.is_valid(d) {
+            return 1;
         }
 
-        let groups_vec = Puzzle::group_vec(from_utf8(&d[..off]).unwrap());
+        let groups_vec = Puzzle::group_vec(&d[..off]);
 
         if groups_vec.len() > self.g.len() {
             return 0;
@@ -85,9 +76,8 @@ impl Puzzle {
 
         let left_group = self.g[groups_vec.len()..].to_vec();
 
-        let s = String::from_utf8(d[off..].to_vec()).unwrap();
-        if let Some(prev) = cache.get(&(s.clone(), left_group.clone())) {
-            println!("Hit for {} {:?} = {}", s, left_group, *prev);
+        let key = (d[off..].to_vec(), left_group);
+        if let Some(prev) = cache.get(&key) {
             return *prev;
         }
 
@@ -97,7 +87,7 @@ impl Puzzle {
                 ret += [b'.', b'#']
                     .iter()
                     .map(|v| {
-                        let mut local = d.clone();
+                        let mut local = d.to_vec();
                         local[x] = *v;
                         self._walk(&local, off + 1, cache)
                     })
@@ -108,9 +98,7 @@ impl Puzzle {
         }
 
         if d[off + 1..].contains(&b'?') {
-            let s = String::from_utf8(d[off..].to_vec()).unwrap();
-            cache.insert((s.clone(), left_group.clone()), ret);
-            //println!("{} @ {:?} == {}", s, left_group, ret);
+            cache.insert(key, ret);
         }
 
         ret
@@ -118,32 +106,73 @@ impl Puzzle {
 
     fn walk(&self) -> usize {
         let d = self.d.clone();
-        let mut cache: HashMap<(String, Vec<i32>), usize> = HashMap::new();
+        let mut cache: HashMap<(Vec<u8>, Vec<i32>), usize> = HashMap::new();
         self._walk(&d, 0, &mut cache)
     }
+
+    /* Tries every way to fill the '?' */
+    fn count_brute(&self) -> usize {
+        let unknown: Vec<usize> = (0..self.d.len()).filter(|x| self.d[*x] == b'?').collect();
+        let mut d = self.d.clone();
+
+        (0..1u64 << unknown.len())
+            .filter(|bits| {
+                for (n, x) in unknown.iter().enumerate() {
+                    d[*x] = if bits & (1 << n) != 0 { b'#' } else { b'.' };
+                }
+                self.is_valid(&d)
+            })
+            .count()
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let g: Vec<String> = self.g.iter().map(|v| v.to_string()).collect();
+        write!(f, "{} {}", String::from_utf8_lossy(&self.d), g.join(","))
+    }
 }
 
 fn main() {
-    let f = File::open("data.txt").unwrap();
-    let mut b = BufReader::new(f);
+    /* d12 --gen SEED [ROWS] [LEN] [UNKNOWN%] prints random spring rows */
+    if let Some(a) = Args::get("--gen") {
+        let mut rng = Rng::new(a.need(0, "a seed"));
+        print!(
+            "{}",
+            gen::springs(&mut rng, a.num(1, 100), a.num(2, 20), a.num(3, 50))
+        );
+        return;
+    }
 
-    let mut data: String = String::new();
+    /* d12 --diff [RUNS] [LEN] [UNKNOWN%] checks the memoized walk
+    against trying every arrangement of single rows */
+    if let Some(a) = Args::get("--diff") {
+        let d = diff::Arrangements {
+            len: a.num(1, 12),
+            unknown: a.num(2, 50),
+        };
+        aoc::diff::run(&d, a.num(0, 100));
+        return;
+    }
 
-    b.read_to_string(&mut data).unwrap();
+    /* Each row is solved as it is read, folded and unfolded */
+    let (mut folded, mut unfolded) = (0, 0);
 
-    let mut puz: Vec<Puzzle> = data.split('\n').map(Puzzle::new).collect();
+    for (x, l) in aoc::input::open("data.txt").unwrap().lines().enumerate() {
+        let mut p = match Puzzle::new(&l.unwrap()) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("Bad row at {}", e.at_line(x + 1));
+                std::process::exit(1);
+            }
+        };
 
-    println!("Part 1 : {}", puz.iter().map(|p| p.walk()).sum::<usize>());
+        folded += p.walk();
 
-    puz.iter_mut().for_each(|v| v.unfold());
+        p.unfold();
+        unfolded += p.walk();
+    }
 
-    println!(
-        "Part 2 : {}",
-        puz.iter()
-            .map(|p| {
-                println!("Line {:?}", p);
-                p.walk()
-            })
-            .sum::<usize>()
-    );
+    aoc::ledger::answer(1, folded);
+    aoc::ledger::answer(2, unfolded);
 }
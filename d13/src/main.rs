@@ -1,14 +1,12 @@
 use std::collections::hash_map::DefaultHasher;
-use std::fs::File;
 use std::hash::{Hash, Hasher};
-use std::io::{BufReader, Read};
+
+use aoc::grid::Grid;
 
 #[derive(Debug)]
 struct Entry {
-    lines: Vec<Vec<u8>>,
-    collumns: Vec<Vec<u8>>,
-    w: usize,
-    h: usize,
+    lines: Grid<u8>,
+    collumns: Grid<u8>,
 }
 
 impl Entry {
@@ -18,8 +16,8 @@ impl Entry {
         hasher.finish()
     }
 
-    fn symetry_index(v: &Vec<Vec<u8>>) -> i32 {
-        let hashes: Vec<u64> = v.iter().map(|v| Entry::hash(v)).collect();
+    fn symetry_index(v: &Grid<u8>) -> i32 {
+        let hashes: Vec<u64> = v.rows_iter().map(Entry::hash).collect();
 
         for i in 1..hashes.len() {
             let mut all_ok: bool = true;
@@ -48,7 +46,9 @@ impl Entry {
         Some(a.iter().zip(b).filter(|(a, b)| *a != *b).count())
     }
 
-    fn axis_with_exactly_one_smudge(v: &Vec<Vec<u8>>) -> i32 {
+    fn axis_with_exactly_one_smudge(v: &Grid<u8>) -> i32 {
+        let v: Vec<&[u8]> = v.rows_iter().collect();
+
         for i in 1..v.len() {
             let mut sum_diff: usize = 0;
 
@@ -56,8 +56,8 @@ impl Entry {
                 let a = v.get(i - j);
                 let b = v.get(i + j - 1);
 
-                if a.is_some() && b.is_some() {
-                    if let Some(c) = Entry::count_diff(a.unwrap(), b.unwrap()) {
+                if let (Some(a), Some(b)) = (a, b) {
+                    if let Some(c) = Entry::count_diff(a, b) {
                         sum_diff += c;
                     }
                 }
@@ -86,49 +86,25 @@ impl Entry {
     }
 
     fn new(data: &str) -> Entry {
-        let mut ret = Entry {
-            lines: Vec::new(),
-            collumns: Vec::new(),
-            w: 0,
-            h: 0,
-        };
-
-        for l in data.split('\n') {
-            let ldata = l.trim().as_bytes().to_vec();
-            if ret.w == 0 {
-                ret.w = ldata.len();
-            } else {
-                assert!(ret.w == ldata.len());
-            }
-            ret.lines.push(ldata);
-            ret.h += 1;
-        }
+        let lines = Grid::parse(data).unwrap();
 
-        /* Now we have loaded the lines we want to generate the collums */
-        for i in 0..ret.w {
-            let cdata = ret.lines.iter().map(|v| v[i]).collect::<Vec<u8>>();
-            ret.collumns.push(cdata);
-        }
+        /* Columns are checked as the lines of the transposed pattern */
+        let collumns = lines.transpose().to_grid();
 
-        ret
+        Entry { lines, collumns }
     }
 }
 
 fn main() {
-    let file = File::open("data.txt").unwrap();
-    let mut r = BufReader::new(file);
-    let mut data = String::new();
-
-    r.read_to_string(&mut data).unwrap();
-
     let mut entries: Vec<Entry> = Vec::new();
 
-    for dat in data.split("\n\n") {
-        entries.push(Entry::new(dat));
+    for block in aoc::input::open("data.txt").unwrap().blocks() {
+        entries.push(Entry::new(&block.unwrap().join("\n")));
     }
 
     let sum: u64 = entries.iter().map(|v| v.score()).sum();
     let sumsmudge: u64 = entries.iter().map(|v| v.score_one_smudge()).sum();
 
-    println!("Part 1 : {} Part 2 : {}", sum, sumsmudge);
+    aoc::ledger::answer(1, sum);
+    aoc::ledger::answer(2, sumsmudge);
 }
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use aoc::diff::Differential;
+use aoc::grid::Grid;
+use aoc::rng::Rng;
+
+use crate::{gen, Edge, Map};
+
+const CYCLE: [Edge; 4] = [Edge::North, Edge::West, Edge::South, Edge::East];
+
+/* A platform and how many spin cycles it goes through */
+#[derive(Clone)]
+pub struct Case {
+    grid: Grid<u8>,
+    cycles: u64,
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "N W S E x{}", self.cycles)?;
+        write!(f, "{}", self.grid)
+    }
+}
+
+/* Skipping cycles once a layout repeats against spinning every cycle */
+pub struct Spin {
+    pub rows: u64,
+    pub cols: u64,
+    pub round: u64,
+    pub cube: u64,
+    pub cycles: u64,
+}
+
+fn loads(m: &Map) -> Vec<i64> {
+    CYCLE.iter().map(|e| m.load(*e)).collect()
+}
+
+impl Differential for Spin {
+    type Input = Case;
+    type Output = Vec<i64>;
+
+    fn generate(&self, rng: &mut Rng) -> Case {
+        let data = gen::platform(rng, self.rows, self.cols, self.round, self.cube);
+
+        Case {
+            grid: Grid::parse(&data).unwrap(),
+            cycles: 1 + rng.below(self.cycles),
+        }
+    }
+
+    fn shrink(&self, c: &Case) -> Vec<Case> {
+        let mut ret = Vec::new();
+        let g = &c.grid;
+
+        for cycles in [c.cycles / 2, c.cycles - 1] {
+            if cycles > 0 {
+                ret.push(Case {
+                    grid: g.clone(),
+                    cycles,
+                });
+            }
+        }
+
+        /* Without one row or one column */
+        if g.rows() > 1 {
+            for r in 0..g.rows() {
+                let rows = g.rows_iter().enumerate().filter(|(x, _)| *x != r);
+                ret.push(Case {
+                    grid: Grid::from_rows(rows.map(|(_, v)| v.to_vec())).unwrap(),
+                    cycles: c.cycles,
+                });
+            }
+        }
+
+        if g.cols() > 1 {
+            for col in 0..g.cols() {
+                let rows = g.rows_iter().map(|v| {
+                    v.iter()
+                        .enumerate()
+                        .filter(|(y, _)| *y != col)
+                        .map(|(_, v)| *v)
+                        .collect()
+                });
+                ret.push(Case {
+                    grid: Grid::from_rows(rows).unwrap(),
+                    cycles: c.cycles,
+                });
+            }
+        }
+
+        /* One rock less */
+        for (pos, v) in g.cells() {
+            if *v != b'.' {
+                let mut grid = g.clone();
+                grid[pos] = b'.';
+                ret.push(Case {
+                    grid,
+                    cycles: c.cycles,
+                });
+            }
+        }
+
+        ret
+    }
+
+    fn fast(&self, c: &Case) -> Vec<i64> {
+        let mut m = Map { m: c.grid.clone() };
+        m.repeat(&CYCLE, c.cycles, false);
+        loads(&m)
+    }
+
+    fn reference(&self, c: &Case) -> Vec<i64> {
+        let mut m = Map { m: c.grid.clone() };
+        m.simulate(&CYCLE, c.cycles);
+        loads(&m)
+    }
+}
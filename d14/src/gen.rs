@@ -0,0 +1,24 @@
+use aoc::rng::Rng;
+
+/* rows x cols platform with round rocks and cube rocks
+making respectively `round` and `cube` percent of the tiles */
+pub fn platform(rng: &mut Rng, rows: u64, cols: u64, round: u64, cube: u64) -> String {
+    let mut ret = String::new();
+
+    for _ in 0..rows {
+        for _ in 0..cols {
+            let v = rng.below(100);
+
+            ret.push(if v < round {
+                'O'
+            } else if v < round + cube {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        ret.push('\n');
+    }
+
+    ret
+}
@@ -1,86 +1,147 @@
-use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
-use std::hash::{Hash, Hasher};
 use std::io::{BufReader, Read};
 
-use array2d::Array2D;
+use aoc::args::Args;
+use aoc::geom::Dir;
+use aoc::grid::Grid;
+use aoc::rng::Rng;
 
-#[derive(Debug)]
-struct Map {
-    m: Array2D<char>,
+mod diff;
+mod gen;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    North,
+    West,
+    South,
+    East,
 }
 
-impl Map {
-    fn new(data: &str) -> Map {
-        let mut lines: Vec<Vec<char>> = Vec::new();
-        for l in data.split('\n') {
-            let v: Vec<char> = l.as_bytes().iter().map(|v| *v as char).collect();
-            lines.push(v);
+impl Edge {
+    fn parse(s: &str) -> Option<Edge> {
+        match s {
+            "N" => Some(Edge::North),
+            "W" => Some(Edge::West),
+            "S" => Some(Edge::South),
+            "E" => Some(Edge::East),
+            _ => None,
         }
+    }
 
-        Map {
-            m: Array2D::from_rows(&lines).unwrap(),
+    fn dir(&self) -> Dir {
+        match self {
+            Edge::North => Dir::N,
+            Edge::West => Dir::W,
+            Edge::South => Dir::S,
+            Edge::East => Dir::E,
         }
     }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Edge::North => "N",
+            Edge::West => "W",
+            Edge::South => "S",
+            Edge::East => "E",
+        };
+        write!(f, "{}", name)
+    }
+}
 
-    fn print(&self) {
-        println!("========");
-        for x in 0..self.m.row_len() {
-            for y in 0..self.m.column_len() {
-                let v = self.m.get(x, y).unwrap();
-                print!("{}", v);
+/* A group of tilts applied `repeat` times in a row */
+#[derive(Debug)]
+struct Step {
+    tilts: Vec<Edge>,
+    repeat: u64,
+}
+
+/* Tilt program such as "N W S E x1000000000" or "N N E",
+a "xK" token repeats all the tilts since the previous "xK" */
+#[derive(Debug)]
+struct Program {
+    steps: Vec<Step>,
+}
+
+impl Program {
+    fn parse(src: &str) -> Result<Program, String> {
+        let mut steps: Vec<Step> = Vec::new();
+        let mut tilts: Vec<Edge> = Vec::new();
+
+        for tok in src.split_whitespace() {
+            if let Some(count) = tok.strip_prefix('x') {
+                let repeat = count
+                    .parse::<u64>()
+                    .map_err(|_| format!("Bad repeat count '{}'", tok))?;
+
+                if tilts.is_empty() {
+                    return Err(format!("Nothing to repeat before '{}'", tok));
+                }
+
+                steps.push(Step {
+                    tilts: std::mem::take(&mut tilts),
+                    repeat,
+                });
+            } else if let Some(e) = Edge::parse(tok) {
+                tilts.push(e);
+            } else {
+                return Err(format!("Unknown tilt '{}'", tok));
             }
-            println!();
         }
+
+        /* Trailing tilts are applied once each */
+        for e in tilts {
+            steps.push(Step {
+                tilts: vec![e],
+                repeat: 1,
+            });
+        }
+
+        Ok(Program { steps })
     }
+}
+
+#[derive(Debug)]
+struct Map {
+    m: Grid<u8>,
+}
 
-    fn aply_move(&mut self, pos: (usize, usize), dir: (i64, i64)) -> bool {
-        let mut tx: i64 = pos.0 as i64;
-        let mut ty: i64 = pos.1 as i64;
+impl Map {
+    fn new(data: &str) -> Map {
+        Map {
+            m: Grid::parse(data).unwrap(),
+        }
+    }
+
+    fn aply_move(&mut self, pos: (usize, usize), dir: Dir) -> bool {
+        let mut cur = pos;
 
         let mut did_move = false;
 
-        loop {
-            let origx = tx as usize;
-            let origy = ty as usize;
-            tx += dir.0;
-            ty += dir.1;
-
-            let mut can_move = false;
-
-            if let Some(v) = self.m.get(tx as usize, ty as usize) {
-                if *v == '.' {
-                    can_move = true;
-                } else {
-                    break;
-                }
-            } else {
+        while let Some(next) = self.m.step(cur, dir) {
+            if self.m[next] != b'.' {
                 break;
             }
 
-            if can_move {
-                self.m.set(origx, origy, '.').unwrap();
-                self.m.set(tx as usize, ty as usize, 'O').unwrap();
-                did_move = true;
-            }
+            self.m[cur] = b'.';
+            self.m[next] = b'O';
+            cur = next;
+            did_move = true;
         }
 
         did_move
     }
 
-    fn move_blocks(&mut self, dir: (i64, i64)) {
+    fn move_blocks(&mut self, dir: Dir) {
         loop {
             let mut did_move = false;
 
-            for x in 0..self.m.row_len() {
-                for y in 0..self.m.column_len() {
-                    let v = self.m.get(x, y).unwrap();
-                    if *v == 'O' {
-                        if self.aply_move((x, y), dir) {
-                            did_move = true;
-                        }
-                    }
+            for pos in self.m.positions() {
+                if self.m[pos] == b'O' && self.aply_move(pos, dir) {
+                    did_move = true;
                 }
             }
 
@@ -90,47 +151,81 @@ impl Map {
         }
     }
 
-    fn hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.m.hash(&mut hasher);
-        hasher.finish()
+    fn tilt(&mut self, e: Edge) {
+        self.move_blocks(e.dir());
     }
 
-    fn period(&mut self) -> (u64, u64, u64) {
-        let mut ret: u64 = 0;
+    /* Apply the tilts n times, once a layout comes back
+    the remaining iterations are skipped using the period,
+    with trace the board is shown after every tilt done */
+    fn repeat(&mut self, tilts: &[Edge], n: u64, trace: bool) {
+        let mut known_layouts: HashMap<Grid<u8>, u64> = HashMap::new();
+        let mut history: Vec<Grid<u8>> = Vec::new();
+
+        for i in 0..n {
+            if let Some(start) = known_layouts.get(&self.m) {
+                let period = i - start;
+                let target = start + (n - start) % period;
+                self.m = history[target as usize].clone();
+
+                if trace {
+                    println!(
+                        "-- after {} same as after {}, period {}, skipping to {}",
+                        i, start, period, n
+                    );
+                    print!("{}", self);
+                }
+                return;
+            }
 
-        let mut known_layouts: HashMap<u64, u64> = HashMap::new();
+            known_layouts.insert(self.m.clone(), i);
+            history.push(self.m.clone());
 
-        loop {
-            ret += 1;
-            self.cycle();
-            let new = self.hash();
+            for e in tilts.iter() {
+                self.tilt(*e);
 
-            if let Some(v) = known_layouts.get(&new) {
-                println!("{} is resonating with {}", ret, v);
-                return (ret, *v, ret - *v);
-            } else {
-                known_layouts.insert(new, ret);
+                if trace {
+                    println!("-- {} tilt {}", i + 1, e);
+                    print!("{}", self);
+                }
             }
         }
     }
 
-    fn cycle(&mut self) {
-        self.move_blocks((-1, 0));
-        self.move_blocks((0, -1));
-        self.move_blocks((1, 0));
-        self.move_blocks((0, 1));
+    /* Same as repeat without the shortcut */
+    fn simulate(&mut self, tilts: &[Edge], n: u64) {
+        for _ in 0..n {
+            for e in tilts.iter() {
+                self.tilt(*e);
+            }
+        }
+    }
+
+    fn run(&mut self, p: &Program, trace: bool) {
+        for s in p.steps.iter() {
+            if trace {
+                let tilts: Vec<String> = s.tilts.iter().map(|v| v.to_string()).collect();
+                println!("== {} x{}", tilts.join(" "), s.repeat);
+            }
+
+            self.repeat(&s.tilts, s.repeat, trace);
+        }
     }
 
-    fn score(&self) -> i64 {
+    fn load(&self, e: Edge) -> i64 {
+        let rows = self.m.rows();
+        let cols = self.m.cols();
+
         let mut ret: i64 = 0;
-        for y in 0..self.m.column_len() {
-            for x in 0..self.m.row_len() {
-                let weight: i64 = (self.m.row_len() - x) as i64;
-                let v = self.m.get(x, y).unwrap();
-                if *v == 'O' {
-                    ret += weight;
-                }
+        for ((x, y), v) in self.m.cells() {
+            let weight = match e {
+                Edge::North => rows - x,
+                Edge::South => x + 1,
+                Edge::West => cols - y,
+                Edge::East => y + 1,
+            };
+            if *v == b'O' {
+                ret += weight as i64;
             }
         }
 
@@ -138,7 +233,43 @@ impl Map {
     }
 }
 
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.m)
+    }
+}
+
 fn main() {
+    /* d14 --gen SEED [ROWS] [COLS] [ROUND%] [CUBE%] prints a random platform */
+    if let Some(a) = Args::get("--gen") {
+        let mut rng = Rng::new(a.need(0, "a seed"));
+        print!(
+            "{}",
+            gen::platform(
+                &mut rng,
+                a.num(1, 100),
+                a.num(2, 100),
+                a.num(3, 15),
+                a.num(4, 10)
+            )
+        );
+        return;
+    }
+
+    /* d14 --diff [RUNS] [ROWS] [COLS] [ROUND%] [CUBE%] [CYCLES] checks the
+    period shortcut against running up to CYCLES spin cycles in full */
+    if let Some(a) = Args::get("--diff") {
+        let d = diff::Spin {
+            rows: a.num(1, 8),
+            cols: a.num(2, 8),
+            round: a.num(3, 30),
+            cube: a.num(4, 15),
+            cycles: a.num(5, 1000),
+        };
+        aoc::diff::run(&d, a.num(0, 100));
+        return;
+    }
+
     let f = File::open("data.txt").unwrap();
 
     let mut r = BufReader::new(f);
@@ -147,20 +278,153 @@ fn main() {
 
     r.read_to_string(&mut data).unwrap();
 
-    let mut part1 = Map::new(&data);
-    part1.move_blocks((-1, 0));
-    println!("Part 1 Score is {}", part1.score());
+    /* d14 [--trace] [PROGRAM...] runs a custom tilt program */
+    let mut trace = false;
+    let mut src: Vec<String> = Vec::new();
 
-    let mut part2_per = Map::new(&data);
+    for a in std::env::args().skip(1) {
+        if a == "--trace" {
+            trace = true;
+        } else {
+            src.push(a);
+        }
+    }
 
-    let period = part2_per.period();
-    println!("Period is {:?}", period);
+    if !src.is_empty() {
+        let p = match Program::parse(&src.join(" ")) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let mut m = Map::new(&data);
+        m.run(&p, trace);
+
+        for e in [Edge::North, Edge::West, Edge::South, Edge::East] {
+            println!("Load on {} edge is {}", e, m.load(e));
+        }
+        return;
+    }
+
+    let mut part1 = Map::new(&data);
+    part1.run(&Program::parse("N").unwrap(), trace);
+    aoc::ledger::answer(1, part1.load(Edge::North));
 
     let mut part2 = Map::new(&data);
+    part2.run(&Program::parse("N W S E x1000000000").unwrap(), trace);
+    aoc::ledger::answer(2, part2.load(Edge::North));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....
+";
+
+    fn run(data: &str, src: &str) -> Map {
+        let mut m = Map::new(data);
+        m.run(&Program::parse(src).unwrap(), false);
+        m
+    }
+
+    #[test]
+    fn example() {
+        assert_eq!(run(EXAMPLE, "N").load(Edge::North), 136);
+        assert_eq!(run(EXAMPLE, "N W S E x1000000000").load(Edge::North), 64);
+    }
+
+    #[test]
+    fn programs() {
+        let p = Program::parse("N W S E x1000000000").unwrap();
+        assert_eq!(p.steps.len(), 1);
+        assert_eq!(
+            p.steps[0].tilts,
+            [Edge::North, Edge::West, Edge::South, Edge::East]
+        );
+        assert_eq!(p.steps[0].repeat, 1000000000);
+
+        /* Trailing tilts become steps of their own */
+        let p = Program::parse("S x2 N E").unwrap();
+        let steps: Vec<(Vec<Edge>, u64)> =
+            p.steps.into_iter().map(|s| (s.tilts, s.repeat)).collect();
+        assert_eq!(
+            steps,
+            [
+                (vec![Edge::South], 2),
+                (vec![Edge::North], 1),
+                (vec![Edge::East], 1)
+            ]
+        );
+
+        assert!(Program::parse("").unwrap().steps.is_empty());
+    }
+
+    #[test]
+    fn program_errors() {
+        for (src, msg) in [
+            ("x3", "Nothing to repeat before 'x3'"),
+            ("N x2 x3", "Nothing to repeat before 'x3'"),
+            ("N x", "Bad repeat count 'x'"),
+            ("N xy", "Bad repeat count 'xy'"),
+            ("N x-1", "Bad repeat count 'x-1'"),
+            ("N Q", "Unknown tilt 'Q'"),
+            ("n", "Unknown tilt 'n'"),
+            ("NW", "Unknown tilt 'NW'"),
+        ] {
+            assert_eq!(Program::parse(src).unwrap_err(), msg, "{}", src);
+        }
+    }
+
+    /* Each rock weighs its distance to the far edge, counting from 1 */
+    #[test]
+    fn edge_loads() {
+        let m = Map::new("O..\n.#O\n");
+
+        assert_eq!(m.load(Edge::North), 2 + 1);
+        assert_eq!(m.load(Edge::South), 1 + 2);
+        assert_eq!(m.load(Edge::West), 3 + 1);
+        assert_eq!(m.load(Edge::East), 1 + 3);
 
-    for _ in 0..(1000000000 - period.0) % period.2 + period.1 {
-        part2.cycle();
+        let m = run("O..\n.#O\n", "E");
+        assert_eq!(m.to_string(), "..O\n.#O\n");
+        assert_eq!(m.load(Edge::East), 3 + 3);
+        assert_eq!(m.load(Edge::West), 1 + 1);
     }
 
-    println!("Part 2 Score is {}", part2.score());
+    /* The period shortcut lands on the layout of a full run,
+    including counts below, at and just past the period */
+    #[test]
+    fn repeat_matches_simulate() {
+        let edges = [Edge::North, Edge::West, Edge::South, Edge::East];
+
+        for seed in 0..40 {
+            let mut rng = Rng::new(seed);
+            let (rows, cols) = (1 + rng.below(6), 1 + rng.below(6));
+            let data = gen::platform(&mut rng, rows, cols, 30, 15);
+
+            let tilts: Vec<Edge> = (0..1 + rng.below(4)).map(|_| *rng.pick(&edges)).collect();
+
+            for n in 0..25 {
+                let mut fast = Map::new(&data);
+                fast.repeat(&tilts, n, false);
+
+                let mut slow = Map::new(&data);
+                slow.simulate(&tilts, n);
+
+                assert_eq!(fast.m, slow.m, "seed {} n {}\n{}", seed, n, data);
+            }
+        }
+    }
 }
@@ -0,0 +1,250 @@
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/* The Holiday ASCII String Helper algorithm as a Hasher,
+every byte does state = ((state + byte) * 17) % 256 */
+#[derive(Debug, Default, Clone)]
+pub struct HolidayHasher {
+    state: u64,
+}
+
+impl Hasher for HolidayHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for v in bytes.iter() {
+            self.state = ((self.state + *v as u64) * 17) % 256;
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+impl HolidayHasher {
+    pub fn hash(d: &[u8]) -> u64 {
+        let mut h = HolidayHasher::default();
+        h.write(d);
+        h.finish()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BuildHolidayHasher;
+
+impl BuildHasher for BuildHolidayHasher {
+    type Hasher = HolidayHasher;
+
+    fn build_hasher(&self) -> HolidayHasher {
+        HolidayHasher::default()
+    }
+}
+
+/* Note str hashing appends a 0xff terminator, Label
+only feeds the raw bytes so the hash matches the puzzle */
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Label(pub String);
+
+impl Hash for Label {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(self.0.as_bytes());
+    }
+}
+
+impl From<&str> for Label {
+    fn from(s: &str) -> Label {
+        Label(s.to_string())
+    }
+}
+
+/* Hash map keeping insertion order inside each bucket,
+replacing a value keeps its slot, removing shifts the others */
+#[derive(Debug, Clone)]
+pub struct HolidayMap<K, V, S = BuildHolidayHasher> {
+    buckets: Vec<Vec<(K, V)>>,
+    build: S,
+    len: usize,
+}
+
+impl<K: Hash + Eq, V> HolidayMap<K, V> {
+    pub fn new() -> HolidayMap<K, V> {
+        HolidayMap::with_hasher(256, BuildHolidayHasher)
+    }
+}
+
+impl<K: Hash + Eq, V> Default for HolidayMap<K, V> {
+    fn default() -> Self {
+        HolidayMap::new()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HolidayMap<K, V, S> {
+    pub fn with_hasher(num_buckets: usize, build: S) -> HolidayMap<K, V, S> {
+        assert!(num_buckets > 0);
+        let mut buckets: Vec<Vec<(K, V)>> = Vec::with_capacity(num_buckets);
+
+        for _ in 0..num_buckets {
+            buckets.push(Vec::new());
+        }
+
+        HolidayMap {
+            buckets,
+            build,
+            len: 0,
+        }
+    }
+
+    pub fn bucket_of<Q>(&self, k: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        (self.build.hash_one(k) % self.buckets.len() as u64) as usize
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.buckets[self.bucket_of(k)]
+            .iter()
+            .find(|v| v.0.borrow() == k)
+            .map(|v| &v.1)
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(k).is_some()
+    }
+
+    pub fn insert(&mut self, k: K, val: V) -> Option<V> {
+        let b = self.bucket_of(&k);
+
+        for v in self.buckets[b].iter_mut() {
+            if v.0 == k {
+                return Some(std::mem::replace(&mut v.1, val));
+            }
+        }
+
+        self.buckets[b].push((k, val));
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let b = self.bucket_of(k);
+        let idx = self.buckets[b].iter().position(|v| v.0.borrow() == k)?;
+        self.len -= 1;
+        Some(self.buckets[b].remove(idx).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /* Entries in bucket order then insertion order */
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets
+            .iter()
+            .flat_map(|b| b.iter().map(|(k, v)| (k, v)))
+    }
+
+    pub fn buckets(&self) -> impl Iterator<Item = (usize, &[(K, V)])> {
+        self.buckets.iter().enumerate().map(|(x, b)| (x, &b[..]))
+    }
+
+    /* Sum of (bucket + 1) * (slot + 1) * value */
+    pub fn focusing_power(&self) -> u64
+    where
+        V: Copy + Into<u64>,
+    {
+        let mut ret: u64 = 0;
+
+        for (bx, b) in self.buckets.iter().enumerate() {
+            for (slot, v) in b.iter().enumerate() {
+                ret += (bx as u64 + 1) * (slot as u64 + 1) * v.1.into();
+            }
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aoc::rng::Rng;
+
+    use super::*;
+
+    /* Few short labels so replacing and removing happen a lot */
+    fn label(rng: &mut Rng) -> Label {
+        let len = 1 + rng.index(3);
+        Label((0..len).map(|_| *rng.pick(&['a', 'b', 'c'])).collect())
+    }
+
+    #[test]
+    fn matches_hashmap() {
+        /* Few buckets so they hold several lenses at once */
+        for (seed, buckets) in (0..50).zip([256, 4].into_iter().cycle()) {
+            let mut rng = Rng::new(seed);
+            let mut map: HolidayMap<Label, u64> =
+                HolidayMap::with_hasher(buckets, BuildHolidayHasher);
+            let mut reference: HashMap<Label, u64> = HashMap::new();
+            /* Insertion order, replacing keeps the slot */
+            let mut order: Vec<(Label, u64)> = Vec::new();
+
+            for _ in 0..200 {
+                let k = label(&mut rng);
+
+                if rng.chance(30) {
+                    assert_eq!(map.remove(&k), reference.remove(&k));
+                    order.retain(|v| v.0 != k);
+                } else {
+                    let v = rng.below(10);
+                    assert_eq!(map.insert(k.clone(), v), reference.insert(k.clone(), v));
+                    match order.iter_mut().find(|e| e.0 == k) {
+                        Some(e) => e.1 = v,
+                        None => order.push((k.clone(), v)),
+                    }
+                }
+
+                assert_eq!(map.len(), reference.len());
+                assert_eq!(map.get(&k), reference.get(&k));
+                for k in reference.keys() {
+                    assert_eq!(map.get(k), reference.get(k));
+                }
+
+                let mut expected = order.clone();
+                expected.sort_by_key(|e| map.bucket_of(&e.0));
+
+                let got: Vec<(Label, u64)> = map.iter().map(|(k, v)| (k.clone(), *v)).collect();
+                assert_eq!(got, expected);
+
+                let mut slots: HashMap<usize, u64> = HashMap::new();
+                let power: u64 = expected
+                    .iter()
+                    .map(|(k, v)| {
+                        let b = map.bucket_of(k);
+                        let slot = slots.entry(b).or_insert(0);
+                        *slot += 1;
+                        (b as u64 + 1) * *slot * v
+                    })
+                    .sum();
+                assert_eq!(map.focusing_power(), power);
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod holiday;
+pub mod ops;
+pub mod trace;
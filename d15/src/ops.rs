@@ -0,0 +1,221 @@
+use std::fmt;
+
+use crate::holiday::{HolidayMap, Label};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    Set { label: Label, focal: u64 },
+    Remove { label: Label },
+}
+
+impl Op {
+    pub fn label(&self) -> &Label {
+        match self {
+            Op::Set { label, .. } | Op::Remove { label } => label,
+        }
+    }
+
+    /* Returns the box affected by the operation */
+    pub fn apply(&self, boxes: &mut HolidayMap<Label, u64>) -> usize {
+        let bucket = boxes.bucket_of(self.label());
+
+        match self {
+            Op::Set { label, focal } => {
+                boxes.insert(label.clone(), *focal);
+            }
+            Op::Remove { label } => {
+                boxes.remove(label);
+            }
+        }
+
+        bucket
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Op::Set { label, focal } => write!(f, "{}={}", label.0, focal),
+            Op::Remove { label } => write!(f, "{}-", label.0),
+        }
+    }
+}
+
+/* One non-empty step of the init sequence, index counts
+every comma separated field so it matches the input */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub index: usize,
+    pub text: String,
+    pub op: Op,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub index: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "step {} \"{}\": {}", self.index, self.text, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_label(l: &str) -> Result<Label, String> {
+    if l.is_empty() {
+        return Err("empty label".to_string());
+    }
+
+    if !l.bytes().all(|v| v.is_ascii_alphabetic()) {
+        return Err(format!("label '{}' is not made of letters", l));
+    }
+
+    Ok(Label::from(l))
+}
+
+fn parse_op(text: &str) -> Result<Op, String> {
+    if let Some(l) = text.strip_suffix('-') {
+        return Ok(Op::Remove {
+            label: parse_label(l)?,
+        });
+    }
+
+    let (l, focal) = text
+        .split_once('=')
+        .ok_or_else(|| "expected 'label=N' or 'label-'".to_string())?;
+
+    let focal = match focal.parse::<u64>() {
+        Ok(v) if (1..=9).contains(&v) => v,
+        _ => return Err(format!("focal length '{}' is not in 1..=9", focal)),
+    };
+
+    Ok(Op::Set {
+        label: parse_label(l)?,
+        focal,
+    })
+}
+
+/* Newlines are ignored anywhere in the sequence and empty steps are skipped */
+pub fn parse_sequence(src: &str) -> Result<Vec<Step>, ParseError> {
+    let mut ret: Vec<Step> = Vec::new();
+
+    for (index, raw) in src.split(',').enumerate() {
+        let text: String = raw.chars().filter(|c| *c != '\n' && *c != '\r').collect();
+        let text = text.trim();
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let op = parse_op(text).map_err(|reason| ParseError {
+            index,
+            text: text.to_string(),
+            reason,
+        })?;
+
+        ret.push(Step {
+            index,
+            text: text.to_string(),
+            op,
+        });
+    }
+
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(src: &str) -> ParseError {
+        parse_sequence(src).unwrap_err()
+    }
+
+    #[test]
+    fn example() {
+        let steps = parse_sequence("rn=1,cm-,qp=3").unwrap();
+
+        assert_eq!(
+            steps.iter().map(|s| s.op.clone()).collect::<Vec<Op>>(),
+            vec![
+                Op::Set {
+                    label: Label::from("rn"),
+                    focal: 1
+                },
+                Op::Remove {
+                    label: Label::from("cm")
+                },
+                Op::Set {
+                    label: Label::from("qp"),
+                    focal: 3
+                },
+            ]
+        );
+        assert_eq!(steps[2].op.to_string(), "qp=3");
+    }
+
+    #[test]
+    fn labels_are_letters() {
+        let e = error("rn=1,c3=2");
+        assert_eq!((e.index, e.text.as_str()), (1, "c3=2"));
+        assert_eq!(e.reason, "label 'c3' is not made of letters");
+
+        assert_eq!(error("=4").reason, "empty label");
+        assert_eq!(error("-").reason, "empty label");
+        assert_eq!(error("a_b-").reason, "label 'a_b' is not made of letters");
+    }
+
+    #[test]
+    fn focal_lengths() {
+        for f in ["0", "10", "-1", "", "x"] {
+            let e = error(&format!("ab={}", f));
+            assert_eq!(e.reason, format!("focal length '{}' is not in 1..=9", f));
+        }
+
+        for f in 1..=9 {
+            assert!(parse_sequence(&format!("ab={}", f)).is_ok());
+        }
+    }
+
+    #[test]
+    fn operation_needed() {
+        for text in ["ab", "ab+3", "ab:3"] {
+            let e = error(text);
+            assert_eq!(e.reason, "expected 'label=N' or 'label-'", "{}", text);
+        }
+    }
+
+    /* Empty fields are skipped but still counted by the index */
+    #[test]
+    fn empty_steps() {
+        assert!(parse_sequence("").unwrap().is_empty());
+        assert!(parse_sequence(",,\n,").unwrap().is_empty());
+
+        let steps = parse_sequence(",rn=1,, cm-").unwrap();
+        assert_eq!(
+            steps.iter().map(|s| s.index).collect::<Vec<usize>>(),
+            [1, 3]
+        );
+
+        let e = error("rn=1,,,x");
+        assert_eq!(e.index, 3);
+        assert_eq!(
+            e.to_string(),
+            "step 3 \"x\": expected 'label=N' or 'label-'"
+        );
+    }
+
+    #[test]
+    fn newlines_ignored() {
+        let steps = parse_sequence("r\nn=1,cm\r\n-,qp=\n3\n").unwrap();
+
+        assert_eq!(
+            steps.iter().map(|s| s.text.as_str()).collect::<Vec<&str>>(),
+            ["rn=1", "cm-", "qp=3"]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::holiday::{HolidayMap, Label};
+
+/* State of the boxes after one step of the init sequence */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub step: usize,
+    pub op: String,
+    pub bucket: usize,
+    pub boxes: Vec<(usize, Vec<(String, u64)>)>,
+}
+
+impl Snapshot {
+    pub fn take(step: usize, op: &str, bucket: usize, map: &HolidayMap<Label, u64>) -> Snapshot {
+        let boxes = map
+            .buckets()
+            .filter(|(_, b)| !b.is_empty())
+            .map(|(x, b)| (x, b.iter().map(|(k, v)| (k.0.clone(), *v)).collect()))
+            .collect();
+
+        Snapshot {
+            step,
+            op: op.to_string(),
+            bucket,
+            boxes,
+        }
+    }
+
+    /* One JSON object on a single line so runs can be diffed */
+    pub fn to_json(&self) -> String {
+        let boxes: Vec<String> = self
+            .boxes
+            .iter()
+            .map(|(x, lenses)| {
+                let lenses: Vec<String> = lenses
+                    .iter()
+                    .map(|(l, f)| format!("[{},{}]", json_str(l), f))
+                    .collect();
+                format!("{{\"box\":{},\"lenses\":[{}]}}", x, lenses.join(","))
+            })
+            .collect();
+
+        format!(
+            "{{\"step\":{},\"op\":{},\"box\":{},\"boxes\":[{}]}}",
+            self.step,
+            json_str(&self.op),
+            self.bucket,
+            boxes.join(",")
+        )
+    }
+}
+
+fn json_str(s: &str) -> String {
+    let mut ret = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+
+    ret.push('"');
+    ret
+}
+
+/* Same layout as the puzzle statement */
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "After \"{}\":", self.op)?;
+
+        for (x, lenses) in self.boxes.iter() {
+            write!(f, "Box {}:", x)?;
+            for (l, v) in lenses.iter() {
+                write!(f, " [{} {}]", l, v)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_puzzle_format() {
+        let mut map = HolidayMap::new();
+        map.insert(Label("rn".to_string()), 1);
+
+        let s = Snapshot::take(0, "rn=1", map.bucket_of(&Label("rn".to_string())), &map);
+        assert_eq!(s.to_string(), "After \"rn=1\":\nBox 0: [rn 1]\n");
+
+        /* The box the step touched is only in the JSON */
+        let s = Snapshot::take(1, "qp-", 1, &map);
+        assert_eq!(s.to_string(), "After \"qp-\":\nBox 0: [rn 1]\n");
+        assert!(s
+            .to_json()
+            .starts_with("{\"step\":1,\"op\":\"qp-\",\"box\":1,"));
+    }
+}
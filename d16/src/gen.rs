@@ -0,0 +1,53 @@
+use aoc::grid::Grid;
+use aoc::rng::Rng;
+
+const ELEMENTS: [u8; 4] = [b'/', b'\\', b'-', b'|'];
+
+/* Rectangle of mirrors a beam goes around clockwise, with a '|' on the
+top side and a '-' below it on the bottom side. Beams split there go
+around and come back to the other splitter so both end up in a loop */
+fn splitter_loop(rng: &mut Rng, g: &mut Grid<u8>) {
+    let (rows, cols) = (g.rows(), g.cols());
+
+    let r1 = rng.index(rows - 2);
+    let r2 = r1 + 2 + rng.index(rows - r1 - 2);
+    let c1 = rng.index(cols - 2);
+    let c2 = c1 + 2 + rng.index(cols - c1 - 2);
+    let c = c1 + 1 + rng.index(c2 - c1 - 1);
+
+    for x in r1..=r2 {
+        g[(x, c1)] = b'.';
+        g[(x, c2)] = b'.';
+        g[(x, c)] = b'.';
+    }
+    g.row_mut(r1)[c1..=c2].fill(b'.');
+    g.row_mut(r2)[c1..=c2].fill(b'.');
+
+    g[(r1, c1)] = b'/';
+    g[(r1, c2)] = b'\\';
+    g[(r2, c2)] = b'/';
+    g[(r2, c1)] = b'\\';
+
+    g[(r1, c)] = b'|';
+    g[(r2, c)] = b'-';
+}
+
+/* rows x cols contraption, `density` percent of the tiles hold a
+random element and `loops` splitter loops are drawn on top */
+pub fn contraption(rng: &mut Rng, rows: u64, cols: u64, density: u64, loops: u64) -> String {
+    assert!(rows >= 3 && cols >= 3, "the grid must be at least 3x3");
+
+    let mut g = Grid::new(rows as usize, cols as usize, b'.');
+
+    for v in g.iter_mut() {
+        if rng.chance(density) {
+            *v = *rng.pick(&ELEMENTS);
+        }
+    }
+
+    for _ in 0..loops {
+        splitter_loop(rng, &mut g);
+    }
+
+    g.to_string()
+}
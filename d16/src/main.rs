@@ -1,132 +1,220 @@
-use array2d::Array2D;
+use aoc::args::Args;
+use aoc::geom::Dir;
+use aoc::grid::Grid;
+use aoc::rng::Rng;
+use aoc::search::{self, Graph, Visited};
 use rayon::prelude::*;
-use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufReader, Read};
 
-struct Map {
-    m: Array2D<u8>,
+mod gen;
+mod optics;
+mod render;
+mod segments;
+use optics::Optics;
+use segments::SegmentGraph;
+
+/* Scratch state of a beam simulation, visited holds one bit
+per (tile, direction) so a tile uses a nibble of a word */
+struct Tracer {
+    visited: Vec<u64>,
+    cols: usize,
 }
 
-impl Map {
-    fn new(data: &str) -> Map {
-        let l: Vec<Vec<u8>> = data.split('\n').map(|v| v.as_bytes().to_vec()).collect();
-
-        let m = Array2D::from_rows(&l).unwrap();
-        Map { m }
-    }
-
-    fn walk(&self, x: i64, y: i64, dir: (i64, i64)) -> usize {
-        let mut e: Array2D<bool> = self.clear_energy();
-        let mut visited: HashSet<String> = HashSet::new();
-        self._walk(x, y, dir, &mut e, &mut visited);
-        Map::energized(&e)
-    }
-
-    fn _walk(
-        &self,
-        x: i64,
-        y: i64,
-        dir: (i64, i64),
-        energy: &mut Array2D<bool>,
-        visited: &mut HashSet<String>,
-    ) {
-        if x < 0 || y < 0 {
-            return;
+impl Tracer {
+    fn new(m: &Map) -> Tracer {
+        let bits = m.m.rows() * m.m.cols() * 4;
+        Tracer {
+            visited: vec![0; bits.div_ceil(64)],
+            cols: m.m.cols(),
         }
+    }
+
+    fn reset(&mut self) {
+        self.visited.fill(0);
+    }
+
+    /* Returns false if the tile was already crossed in this direction */
+    fn visit(&mut self, x: usize, y: usize, dir: Dir) -> bool {
+        let bit = (x * self.cols + y) * 4 + dir.index();
+        let mask = 1u64 << (bit % 64);
 
-        let cur = self.m.get(x as usize, y as usize);
+        let word = &mut self.visited[bit / 64];
+        let seen = *word & mask != 0;
+        *word |= mask;
+        !seen
+    }
+
+    /* Nibble of the directions the tile was crossed in, bits follow Dir::CARDINALS */
+    fn dirs_at(&self, x: usize, y: usize) -> u64 {
+        let bit = (x * self.cols + y) * 4;
+        (self.visited[bit / 64] >> (bit % 64)) & 0xf
+    }
 
-        if cur.is_none() {
-            return;
+    fn energy(&self, m: &Map) -> Grid<bool> {
+        let mut e = Map::clear_energy(m);
+
+        for x in 0..m.m.rows() {
+            for y in 0..m.m.cols() {
+                e[(x, y)] = self.dirs_at(x, y) != 0;
+            }
         }
 
-        let cur = cur.unwrap();
+        e
+    }
+
+    fn energized(&self) -> usize {
+        self.visited
+            .iter()
+            .map(|w| {
+                let any = w | (w >> 1) | (w >> 2) | (w >> 3);
+                (any & 0x1111_1111_1111_1111).count_ones() as usize
+            })
+            .sum()
+    }
+}
+
+impl Visited<((usize, usize), Dir)> for Tracer {
+    fn insert(&mut self, n: &((usize, usize), Dir)) -> bool {
+        let ((x, y), dir) = *n;
+        self.visit(x, y, dir)
+    }
+}
+
+struct Map {
+    m: Grid<u8>,
+    optics: Optics,
+}
 
-        let visit = format!("{:?}x{}y{}", dir, x, y);
+impl Map {
+    fn new(data: &str, optics: Optics) -> Result<Map, String> {
+        let l: Vec<Vec<u8>> = data.lines().map(|v| v.as_bytes().to_vec()).collect();
 
-        if visited.contains(&visit) {
-            return;
+        for (x, row) in l.iter().enumerate() {
+            for (y, v) in row.iter().enumerate() {
+                if !optics.is_known(*v) {
+                    return Err(format!("Unknown tile '{}' at {}:{}", *v as char, x, y));
+                }
+            }
         }
 
-        /* Set cell as energized */
-        energy.set(x as usize, y as usize, true).unwrap();
-
-        visited.insert(visit);
-
-        let new_dir = match *cur {
-            b'.' => (dir, (0, 0)),
-            b'/' => match dir {
-                (-1, 0) => ((0, 1), (0, 0)),
-                (1, 0) => ((0, -1), (0, 0)),
-                (0, 1) => ((-1, 0), (0, 0)),
-                (0, -1) => ((1, 0), (0, 0)),
-                _ => unreachable!(),
-            },
-            b'\\' => match dir {
-                (-1, 0) => ((0, -1), (0, 0)),
-                (1, 0) => ((0, 1), (0, 0)),
-                (0, 1) => ((1, 0), (0, 0)),
-                (0, -1) => ((-1, 0), (0, 0)),
-                _ => unreachable!(),
-            },
-            b'-' => match dir {
-                (0, 1) | (0, -1) => (dir, (0, 0)),
-                (1, 0) | (-1, 0) => ((0, -1), (0, 1)),
-                _ => unreachable!(),
-            },
-            b'|' => match dir {
-                (0, -1) | (0, 1) => ((1, 0), (-1, 0)),
-                (1, 0) | (-1, 0) => (dir, (0, 0)),
-                _ => unreachable!(),
-            },
-            _ => unreachable!(),
-        };
-
-        let d1 = new_dir.0;
-        let d2 = new_dir.1;
-
-        self._walk(x + d1.0, y + d1.1, d1, energy, visited);
-
-        if d2 != (0, 0) {
-            self._walk(x + d2.0, y + d2.1, d2, energy, visited);
+        let m = Grid::from_rows(l).map_err(|e| format!("Map is not rectangular, {}", e))?;
+        Ok(Map { m, optics })
+    }
+
+    /* Tiles crossed by a beam entering at pos, none when pos is off the map */
+    fn walk(&self, pos: (usize, usize), dir: Dir) -> usize {
+        if self.m.get(pos).is_none() {
+            return 0;
         }
+
+        let mut t = Tracer::new(self);
+        self.trace(&mut t, pos, dir);
+        t.energized()
+    }
+
+    fn clear_energy(&self) -> Grid<bool> {
+        Grid::new(self.m.rows(), self.m.cols(), false)
     }
 
-    fn clear_energy(&self) -> Array2D<bool> {
-        Array2D::filled_with(false, self.m.num_rows(), self.m.num_columns())
+    /* Outgoing directions of a beam crossing the tile */
+    fn next_dirs(&self, cur: u8, dir: Dir) -> impl Iterator<Item = Dir> {
+        let outs = self.optics.outs(cur, dir.index());
+        Dir::CARDINALS
+            .into_iter()
+            .filter(move |d| outs & (1 << d.index()) != 0)
     }
 
-    fn energized(energy: &Array2D<bool>) -> usize {
-        energy.elements_row_major_iter().filter(|v| **v).count()
+    /* Follow all the beams from pos, marking them in the tracer */
+    fn trace(&self, t: &mut Tracer, pos: (usize, usize), dir: Dir) {
+        t.reset();
+        search::flood_fill_with(self, (pos, dir), t);
     }
 
-    fn scan_edges(&mut self) -> usize {
-        let mut sources: Vec<((i64, i64), (i64, i64))> = Vec::new();
+    /* Every tile of the border entered from outside, the direction
+    comes from the side so 1 wide maps are entered both ways */
+    fn edge_sources(&self) -> Vec<((usize, usize), Dir)> {
+        let mut sources: Vec<((usize, usize), Dir)> = Vec::new();
 
-        for x in [0, self.m.row_len() - 1] {
-            for y in 0..self.m.column_len() {
-                let dir = if x == 0 { (1, 0) } else { (-1, 0) };
-                sources.push(((x as i64, y as i64), dir));
+        if self.m.rows() == 0 || self.m.cols() == 0 {
+            return sources;
+        }
+
+        for (x, dir) in [(0, Dir::S), (self.m.rows() - 1, Dir::N)] {
+            for y in 0..self.m.cols() {
+                sources.push(((x, y), dir));
             }
         }
 
-        for y in [0, self.m.column_len() - 1] {
-            for x in 0..self.m.row_len() {
-                let dir = if y == 0 { (0, 1) } else { (0, -1) };
-                sources.push(((x as i64, y as i64), dir));
+        for (y, dir) in [(0, Dir::E), (self.m.cols() - 1, Dir::W)] {
+            for x in 0..self.m.rows() {
+                sources.push(((x, y), dir));
             }
         }
 
         sources
+    }
+
+    /* Every edge entry reuses the energy memoized per splitter,
+    None when the map is empty and has no edge */
+    fn scan_edges(&self) -> Option<Best> {
+        let g = SegmentGraph::build(self);
+
+        /* Each rayon worker keeps its own tile buffer */
+        self.edge_sources()
             .par_iter()
-            .map(|source| self.walk(source.0 .0, source.0 .1, source.1))
-            .max()
-            .unwrap()
+            .map_init(
+                || g.new_tileset(),
+                |buf, (pos, dir)| {
+                    g.energize(self, *pos, *dir, buf);
+                    Best {
+                        energized: buf.count(),
+                        pos: *pos,
+                        dir: *dir,
+                    }
+                },
+            )
+            .max_by_key(|b| b.energized)
     }
 }
 
+/* A beam is a tile and its direction of travel */
+impl Graph for Map {
+    type Node = ((usize, usize), Dir);
+
+    fn neighbours(&self, n: &Self::Node) -> Vec<(Self::Node, u64)> {
+        let (pos, dir) = *n;
+
+        self.next_dirs(self.m[pos], dir)
+            .filter_map(|d| self.m.step(pos, d).map(|p| ((p, d), 1)))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct Best {
+    energized: usize,
+    pos: (usize, usize),
+    dir: Dir,
+}
+
 fn main() {
+    /* d16 --gen SEED [ROWS] [COLS] [DENSITY%] [LOOPS] prints a random contraption */
+    if let Some(a) = Args::get("--gen") {
+        let mut rng = Rng::new(a.need(0, "a seed"));
+        print!(
+            "{}",
+            gen::contraption(
+                &mut rng,
+                a.num(1, 110),
+                a.num(2, 110),
+                a.num(3, 10),
+                a.num(4, 3)
+            )
+        );
+        return;
+    }
+
     let f = File::open("data.txt").unwrap();
     let mut r = BufReader::new(f);
 
@@ -134,11 +222,129 @@ fn main() {
 
     r.read_to_string(&mut data).unwrap();
 
-    let mut m = Map::new(&data);
+    /* d16 [--optics FILE] [--beams] [--energy] [--heatmap FILE.ppm] */
+    let mut optics = Optics::standard();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some(p) = args.iter().position(|v| v == "--optics") {
+        let path = args.get(p + 1).expect("--optics needs a file name").clone();
+        if let Err(e) = optics.load(&path) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        args.drain(p..p + 2);
+    }
+
+    let m = match Map::new(&data, optics) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
 
-    println!(
-        "Part 1 energized {} Part 2 : {:?}",
-        m.walk(0, 0, (0, 1)),
-        m.scan_edges()
-    );
+    let mut args = args.into_iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--beams" | "--energy" => {
+                let mut t = Tracer::new(&m);
+                m.trace(&mut t, (0, 0), Dir::E);
+
+                if a == "--beams" {
+                    print!("{}", m.render_beams(&t));
+                } else {
+                    print!("{}", render::render_energy(&t.energy(&m)));
+                }
+            }
+            "--heatmap" => {
+                let path = args.next().expect("--heatmap needs a file name");
+                render::write_ppm(&path, &m.heatmap(), 4).unwrap();
+            }
+            _ => panic!("Unknown argument {}", a),
+        }
+    }
+
+    aoc::ledger::answer(1, m.walk((0, 0), Dir::E));
+
+    match m.scan_edges() {
+        Some(best) => {
+            println!("Best entry at {:?} going {}", best.pos, best.dir);
+            aoc::ledger::answer(2, best.energized);
+        }
+        None => {
+            println!("Empty map, no edge to enter from");
+            aoc::ledger::answer(2, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_sources() {
+        let m = Map::new("", Optics::standard()).unwrap();
+        assert!(m.edge_sources().is_empty());
+        assert!(m.scan_edges().is_none());
+        assert_eq!(m.walk((0, 0), Dir::E), 0);
+
+        let m = Map::new("...\n...", Optics::standard()).unwrap();
+        assert_eq!(m.edge_sources().len(), 2 * 3 + 2 * 2);
+
+        /* A single row is entered from above and from below */
+        let m = Map::new("...", Optics::standard()).unwrap();
+        let src = m.edge_sources();
+        for y in 0..3 {
+            assert!(src.contains(&((0, y), Dir::S)));
+            assert!(src.contains(&((0, y), Dir::N)));
+        }
+        assert!(src.contains(&((0, 0), Dir::E)));
+        assert!(src.contains(&((0, 2), Dir::W)));
+
+        let m = Map::new(".\n.", Optics::standard()).unwrap();
+        let src = m.edge_sources();
+        for x in 0..2 {
+            assert!(src.contains(&((x, 0), Dir::E)));
+            assert!(src.contains(&((x, 0), Dir::W)));
+        }
+    }
+
+    /* The splitter memoization against walking the beams one by one,
+    from every edge of random contraptions with loops and absorbers */
+    #[test]
+    fn energize_matches_walk() {
+        let mut optics = Optics::standard();
+        optics.parse("# N= E= S= W=").unwrap();
+
+        for seed in 0..40 {
+            let mut rng = Rng::new(seed);
+            let (rows, cols) = (3 + rng.below(12), 3 + rng.below(12));
+            let (density, loops) = (10 + rng.below(30), rng.below(4));
+            let data = gen::contraption(&mut rng, rows, cols, density, loops);
+
+            /* Some empty tiles become absorbers */
+            let data: String = data
+                .chars()
+                .map(|c| if c == '.' && rng.chance(5) { '#' } else { c })
+                .collect();
+
+            let m = Map::new(&data, optics.clone()).unwrap();
+            let g = SegmentGraph::build(&m);
+            let mut buf = g.new_tileset();
+
+            for (pos, dir) in m.edge_sources() {
+                g.energize(&m, pos, dir, &mut buf);
+                assert_eq!(
+                    buf.count(),
+                    m.walk(pos, dir),
+                    "seed {} entering {:?} going {}\n{}",
+                    seed,
+                    pos,
+                    dir,
+                    data
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,107 @@
+use std::fs;
+
+use aoc::geom::Dir;
+
+/* Puzzle elements, one line per tile byte giving the outgoing
+directions for each incoming direction of travel, a direction
+left out goes straight through and an empty list absorbs the beam */
+const STANDARD: &str = "\
+. N=N E=E S=S W=W
+/ N=E E=N S=W W=S
+\\ N=W W=N S=E E=S
+- N=WE S=WE
+| E=NS W=NS
+";
+
+/* For each tile byte and incoming direction a bitmask of outgoing directions */
+#[derive(Debug, Clone)]
+pub struct Optics {
+    outs: [[u8; 4]; 256],
+    known: [bool; 256],
+}
+
+impl Optics {
+    pub fn standard() -> Optics {
+        let mut ret = Optics {
+            outs: [[0; 4]; 256],
+            known: [false; 256],
+        };
+        ret.parse(STANDARD).unwrap();
+        ret
+    }
+
+    pub fn outs(&self, tile: u8, dir: usize) -> u8 {
+        self.outs[tile as usize][dir]
+    }
+
+    pub fn is_known(&self, tile: u8) -> bool {
+        self.known[tile as usize]
+    }
+
+    /* Index of a direction of travel, only cardinals are allowed */
+    fn parse_dir(c: char) -> Option<usize> {
+        Dir::parse(&c.to_string())
+            .filter(|d| !d.is_diagonal())
+            .map(Dir::index)
+    }
+
+    fn parse_line(&mut self, l: &str) -> Result<(), String> {
+        let mut toks = l.split_whitespace();
+
+        let tile = toks.next().unwrap();
+        if tile.len() != 1 {
+            return Err(format!("tile '{}' must be a single byte", tile));
+        }
+        let tile = tile.as_bytes()[0];
+
+        /* Straight through unless said otherwise */
+        let mut outs = [1, 2, 4, 8];
+
+        for t in toks {
+            let (from, to) = t
+                .split_once('=')
+                .ok_or_else(|| format!("expected DIR=DIRS got '{}'", t))?;
+
+            let mut chars = from.chars();
+            let from = match (chars.next().and_then(Optics::parse_dir), chars.next()) {
+                (Some(d), None) => d,
+                _ => return Err(format!("bad incoming direction '{}'", from)),
+            };
+
+            let mut mask = 0;
+            for c in to.chars() {
+                let d = Optics::parse_dir(c)
+                    .ok_or_else(|| format!("bad outgoing direction '{}'", c))?;
+                mask |= 1 << d;
+            }
+
+            outs[from] = mask;
+        }
+
+        self.outs[tile as usize] = outs;
+        self.known[tile as usize] = true;
+
+        Ok(())
+    }
+
+    /* Adds or overrides elements, lines starting with ';' are comments */
+    pub fn parse(&mut self, src: &str) -> Result<(), String> {
+        for (x, l) in src.lines().enumerate() {
+            let l = l.trim();
+
+            if l.is_empty() || l.starts_with(';') {
+                continue;
+            }
+
+            self.parse_line(l)
+                .map_err(|e| format!("line {}: {}", x + 1, e))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn load(&mut self, path: &str) -> Result<(), String> {
+        let src = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        self.parse(&src).map_err(|e| format!("{} {}", path, e))
+    }
+}
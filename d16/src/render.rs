@@ -0,0 +1,134 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use aoc::geom::Dir;
+use aoc::grid::Grid;
+use rayon::prelude::*;
+
+use super::segments::SegmentGraph;
+use super::{Map, Tracer};
+
+impl Map {
+    /* Same drawing as the puzzle, empty tiles show the beam
+    direction or the number of beams crossing them */
+    pub fn render_beams(&self, t: &Tracer) -> String {
+        let mut ret = String::new();
+
+        for x in 0..self.m.rows() {
+            for y in 0..self.m.cols() {
+                let cur = *self.m.get((x, y)).unwrap();
+                let dirs = t.dirs_at(x, y);
+
+                let c = if cur != b'.' {
+                    cur as char
+                } else {
+                    match dirs.count_ones() {
+                        0 => '.',
+                        1 => match Dir::CARDINALS[dirs.trailing_zeros() as usize] {
+                            Dir::N => '^',
+                            Dir::E => '>',
+                            Dir::S => 'v',
+                            _ => '<',
+                        },
+                        n => char::from_digit(n, 10).unwrap(),
+                    }
+                };
+
+                ret.push(c);
+            }
+            ret.push('\n');
+        }
+
+        ret
+    }
+
+    /* Number of edge entries energizing each tile */
+    pub fn heatmap(&self) -> Grid<u32> {
+        let g = SegmentGraph::build(self);
+        let cols = self.m.cols();
+        let tiles = self.m.rows() * cols;
+
+        let counts = self
+            .edge_sources()
+            .par_iter()
+            .fold(
+                || (g.new_tileset(), vec![0u32; tiles]),
+                |(mut buf, mut counts), (pos, dir)| {
+                    g.energize(self, *pos, *dir, &mut buf);
+                    for v in buf.iter() {
+                        counts[v] += 1;
+                    }
+                    (buf, counts)
+                },
+            )
+            .map(|(_, counts)| counts)
+            .reduce(
+                || vec![0u32; tiles],
+                |mut a, b| {
+                    for (x, v) in a.iter_mut().zip(b.iter()) {
+                        *x += v;
+                    }
+                    a
+                },
+            );
+
+        Grid::from_vec(counts, self.m.rows(), cols).unwrap()
+    }
+}
+
+pub fn render_energy(e: &Grid<bool>) -> String {
+    let mut ret = String::new();
+
+    for row in e.rows_iter() {
+        for v in row {
+            ret.push(if *v { '#' } else { '.' });
+        }
+        ret.push('\n');
+    }
+
+    ret
+}
+
+/* Black to red to yellow to white */
+fn heat_color(v: u32, max: u32) -> [u8; 3] {
+    if max == 0 {
+        return [0, 0, 0];
+    }
+
+    let t = (v as f64 / max as f64 * 765.0) as u32;
+
+    match t {
+        0..=255 => [t as u8, 0, 0],
+        256..=510 => [255, (t - 255) as u8, 0],
+        _ => [255, 255, (t - 510).min(255) as u8],
+    }
+}
+
+/* Binary PPM with each tile drawn as a scale x scale square */
+pub fn write_ppm(path: &str, heat: &Grid<u32>, scale: usize) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let max = heat.iter().copied().max().unwrap_or(0);
+
+    write!(
+        out,
+        "P6\n{} {}\n255\n",
+        heat.cols() * scale,
+        heat.rows() * scale
+    )?;
+
+    for row in heat.rows_iter() {
+        let line: Vec<u8> = row
+            .iter()
+            .flat_map(|v| {
+                let c = heat_color(*v, max);
+                std::iter::repeat_n(c, scale).flatten()
+            })
+            .collect();
+
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+
+    out.flush()
+}
@@ -0,0 +1,291 @@
+use aoc::geom::Dir;
+
+use super::Map;
+
+/* One bit per tile of the map */
+#[derive(Debug, Clone)]
+pub struct TileSet {
+    bits: Vec<u64>,
+}
+
+impl TileSet {
+    pub fn new(tiles: usize) -> TileSet {
+        TileSet {
+            bits: vec![0; tiles.div_ceil(64)],
+        }
+    }
+
+    pub fn set(&mut self, tile: usize) {
+        self.bits[tile / 64] |= 1 << (tile % 64);
+    }
+
+    pub fn union(&mut self, other: &TileSet) {
+        for (a, b) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *a |= b;
+        }
+    }
+
+    pub fn copy_from(&mut self, other: &TileSet) {
+        self.bits.copy_from_slice(&other.bits);
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(x, w)| {
+            (0..64)
+                .filter(move |b| w & (1 << b) != 0)
+                .map(move |b| x * 64 + b)
+        })
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|v| v.count_ones() as usize).sum()
+    }
+}
+
+/* Run of a beam with a single path (mirrors and pass-through
+splitters included) until it exits, is absorbed or reaches a node */
+#[derive(Debug)]
+pub struct Segment {
+    pub tiles: Vec<usize>,
+    pub end: Option<usize>,
+}
+
+/* Elements sending a beam in several directions are the nodes
+of the graph, a node is a tile and its outgoing directions so
+a splitter emits the same beams whatever side it was hit from
+and the energy of everything downstream is computed once */
+pub struct SegmentGraph {
+    cols: usize,
+    tiles: usize,
+    node_of: Vec<[Option<usize>; 4]>,
+    comp_of: Vec<usize>,
+    energy: Vec<TileSet>,
+}
+
+impl SegmentGraph {
+    pub fn build(m: &Map) -> SegmentGraph {
+        let rows = m.m.rows();
+        let cols = m.m.cols();
+
+        let mut node_of: Vec<[Option<usize>; 4]> = vec![[None; 4]; rows * cols];
+        let mut nodes: Vec<(usize, usize, u8)> = Vec::new();
+
+        for x in 0..rows {
+            for y in 0..cols {
+                let cur = *m.m.get((x, y)).unwrap();
+                let tile = x * cols + y;
+
+                for d in 0..4 {
+                    let outs = m.optics.outs(cur, d);
+                    if outs.count_ones() < 2 {
+                        continue;
+                    }
+
+                    let known = node_of[tile]
+                        .iter()
+                        .flatten()
+                        .find(|n| nodes[**n].2 == outs)
+                        .copied();
+
+                    node_of[tile][d] = Some(known.unwrap_or_else(|| {
+                        nodes.push((x, y, outs));
+                        nodes.len() - 1
+                    }));
+                }
+            }
+        }
+
+        let mut g = SegmentGraph {
+            cols,
+            tiles: rows * cols,
+            node_of,
+            comp_of: Vec::new(),
+            energy: Vec::new(),
+        };
+
+        /* Tiles and successors of each node */
+        let mut own: Vec<TileSet> = Vec::with_capacity(nodes.len());
+        let mut succ: Vec<Vec<usize>> = Vec::with_capacity(nodes.len());
+
+        for (x, y, outs) in nodes.iter() {
+            let mut t = TileSet::new(g.tiles);
+            t.set(x * cols + y);
+
+            let mut s: Vec<usize> = Vec::new();
+
+            for d in Dir::CARDINALS
+                .into_iter()
+                .filter(|d| outs & (1 << d.index()) != 0)
+            {
+                let seg = match m.m.step((*x, *y), d) {
+                    Some(next) => g.segment(m, next, d),
+                    None => continue,
+                };
+                for v in seg.tiles {
+                    t.set(v);
+                }
+                if let Some(e) = seg.end {
+                    s.push(e);
+                }
+            }
+
+            own.push(t);
+            succ.push(s);
+        }
+
+        let comps = SegmentGraph::components(&succ);
+
+        g.comp_of = vec![0; nodes.len()];
+        for (c, members) in comps.iter().enumerate() {
+            for v in members {
+                g.comp_of[*v] = c;
+            }
+        }
+
+        /* Components come sinks first so successors are already known */
+        for (c, members) in comps.iter().enumerate() {
+            let mut e = TileSet::new(g.tiles);
+
+            for v in members.iter() {
+                e.union(&own[*v]);
+                for w in succ[*v].iter() {
+                    let wc = g.comp_of[*w];
+                    if wc != c {
+                        e.union(&g.energy[wc]);
+                    }
+                }
+            }
+
+            g.energy.push(e);
+        }
+
+        g
+    }
+
+    /* Iterative Tarjan, returns the strongly connected
+    components in reverse topological order */
+    fn components(succ: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let n = succ.len();
+        let mut index: Vec<Option<usize>> = vec![None; n];
+        let mut low: Vec<usize> = vec![0; n];
+        let mut on_stack: Vec<bool> = vec![false; n];
+        let mut stack: Vec<usize> = Vec::new();
+        let mut comps: Vec<Vec<usize>> = Vec::new();
+        let mut counter = 0;
+
+        for s in 0..n {
+            if index[s].is_some() {
+                continue;
+            }
+
+            let mut call: Vec<(usize, usize)> = vec![(s, 0)];
+            index[s] = Some(counter);
+            low[s] = counter;
+            counter += 1;
+            stack.push(s);
+            on_stack[s] = true;
+
+            while let Some((v, i)) = call.last_mut() {
+                let v = *v;
+
+                if *i < succ[v].len() {
+                    let w = succ[v][*i];
+                    *i += 1;
+
+                    match index[w] {
+                        None => {
+                            index[w] = Some(counter);
+                            low[w] = counter;
+                            counter += 1;
+                            stack.push(w);
+                            on_stack[w] = true;
+                            call.push((w, 0));
+                        }
+                        Some(iw) if on_stack[w] => low[v] = low[v].min(iw),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                call.pop();
+
+                if let Some((u, _)) = call.last() {
+                    low[*u] = low[*u].min(low[v]);
+                }
+
+                if Some(low[v]) == index[v] {
+                    let mut comp: Vec<usize> = Vec::new();
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w] = false;
+                        comp.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    comps.push(comp);
+                }
+            }
+        }
+
+        comps
+    }
+
+    pub fn segment(&self, m: &Map, pos: (usize, usize), dir: Dir) -> Segment {
+        let (mut pos, mut dir) = (pos, dir);
+        let mut tiles: Vec<usize> = Vec::new();
+
+        /* Mirror only loops cannot be longer than this */
+        for _ in 0..self.tiles * 4 {
+            let tile = pos.0 * self.cols + pos.1;
+            let d = dir.index();
+            let outs = m.optics.outs(m.m[pos], d);
+
+            match outs.count_ones() {
+                0 => {
+                    tiles.push(tile);
+                    break;
+                }
+                1 => {
+                    tiles.push(tile);
+                    dir = Dir::CARDINALS[outs.trailing_zeros() as usize];
+                }
+                _ => {
+                    return Segment {
+                        tiles,
+                        end: self.node_of[tile][d],
+                    }
+                }
+            }
+
+            pos = match m.m.step(pos, dir) {
+                Some(v) => v,
+                None => break,
+            };
+        }
+
+        Segment { tiles, end: None }
+    }
+
+    /* Tiles energized by a beam entering at pos, written in buf */
+    pub fn energize(&self, m: &Map, pos: (usize, usize), dir: Dir, buf: &mut TileSet) {
+        let seg = self.segment(m, pos, dir);
+
+        match seg.end {
+            Some(n) => buf.copy_from(&self.energy[self.comp_of[n]]),
+            None => buf.clear(),
+        }
+
+        for v in seg.tiles {
+            buf.set(v);
+        }
+    }
+
+    pub fn new_tileset(&self) -> TileSet {
+        TileSet::new(self.tiles)
+    }
+}
@@ -1,20 +1,51 @@
-use std::{
-    error::Error,
-    fs::File,
-    io::{BufReader, Read},
-};
+use std::{collections::BTreeMap, error::Error};
 
-#[derive(Debug)]
-struct Obs {
-    r: i32,
-    g: i32,
-    b: i32,
+use aoc::parse::{self, ParseError, Parser};
+
+/* Number of cubes per colour, used for draws and bags */
+type Cubes = BTreeMap<String, i32>;
+
+/* "3 blue, 4 red", counts can't be negative */
+fn parse_cubes(p: &mut Parser) -> Result<Cubes, ParseError> {
+    let mut ret = Cubes::new();
+
+    let draw = p.list(",", |v| {
+        v.skip_ws();
+        let at = *v;
+
+        let cnt = v.number::<i32>()?;
+        if cnt < 0 {
+            return Err(at.error(format!("negative count {}", cnt)));
+        }
+
+        Ok((cnt, v.word()?))
+    })?;
+
+    for (cnt, colour) in draw {
+        *ret.entry(colour.to_string()).or_insert(0) += cnt;
+    }
+
+    Ok(ret)
+}
+
+fn fmt_cubes(c: &Cubes) -> String {
+    let v: Vec<String> = c.iter().map(|(k, v)| format!("{} {}", v, k)).collect();
+    v.join(", ")
+}
+
+/* A draw showing more cubes of a colour than the bag holds */
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Violation {
+    draw: usize,
+    colour: String,
+    seen: i32,
+    available: i32,
 }
 
 #[derive(Debug)]
 struct Game {
     id: i32,
-    views: Vec<Obs>,
+    views: Vec<Cubes>,
 }
 
 impl Game {
@@ -25,94 +56,379 @@ impl Game {
         }
     }
 
-    fn possible(&self, r: i32, g: i32, b: i32) -> bool {
+    /* Colours missing from the bag have no cubes */
+    fn violations(&self, bag: &Cubes) -> Vec<Violation> {
+        let mut ret = Vec::new();
+
+        for (draw, obs) in self.views.iter().enumerate() {
+            for (colour, seen) in obs.iter() {
+                let available = bag.get(colour).copied().unwrap_or(0);
+                if *seen > available {
+                    ret.push(Violation {
+                        draw,
+                        colour: colour.clone(),
+                        seen: *seen,
+                        available,
+                    });
+                }
+            }
+        }
+
+        ret
+    }
+
+    fn possible(&self, bag: &Cubes) -> bool {
+        self.violations(bag).is_empty()
+    }
+
+    /* Smallest bag allowing every draw */
+    fn min(&self) -> Cubes {
+        let mut ret = Cubes::new();
+
         for obs in self.views.iter() {
-            if (obs.r > r) || (obs.g > g) || (obs.b > b) {
-                println!(
-                    "Impossible {} {} {} over {} {} {}",
-                    r, g, b, obs.r, obs.g, obs.b
-                );
-                return false;
+            for (colour, cnt) in obs.iter() {
+                let v = ret.entry(colour.clone()).or_insert(0);
+                *v = (*v).max(*cnt);
             }
         }
 
-        true
+        ret
     }
 
-    fn min(&self) -> (i32, i32, i32) {
-        let r: i32 = self.views.iter().map(|v| v.r).max().unwrap();
-        let g: i32 = self.views.iter().map(|v| v.g).max().unwrap();
-        let b: i32 = self.views.iter().map(|v| v.b).max().unwrap();
+    /* Product of the minimum counts of the bag colours, a colour
+    never drawn counts as 0 cubes and makes the power 0 */
+    fn power(&self, bag: &Cubes) -> i64 {
+        let min = self.min();
+        bag.keys()
+            .map(|c| min.get(c).copied().unwrap_or(0) as i64)
+            .product()
+    }
+
+    fn push(&mut self, p: &mut Parser) -> Result<(), ParseError> {
+        for mut obs in p.split(";") {
+            self.views.push(parse_cubes(&mut obs)?);
+        }
 
-        (r, g, b)
+        Ok(())
     }
+}
+
+/* Smallest bag making all the games possible */
+fn min_bag<'a>(games: impl Iterator<Item = &'a Game>) -> Cubes {
+    let mut ret = Cubes::new();
 
-    fn power(&self) -> i32 {
-        let m = self.min();
-        m.0 * m.1 * m.2
+    for g in games {
+        for (colour, cnt) in g.min() {
+            let v = ret.entry(colour).or_insert(0);
+            *v = (*v).max(cnt);
+        }
     }
 
-    fn push(&mut self, val: String) -> Result<(), Box<dyn Error>> {
-        for obs in val.split(';') {
-            let mut new = Obs { r: 0, g: 0, b: 0 };
+    ret
+}
 
-            for vals in obs.split(',') {
-                let data: Vec<String> = vals.trim().split(' ').map(|v| v.to_string()).collect();
-                if data.len() != 2 {
-                    println!("Error on {}", obs);
-                }
-                let cnt = data[0].parse::<i32>()?;
+/* Smallest bag for the listed games, which must all exist */
+fn min_bag_of(games: &[Game], ids: &[i32]) -> Result<Cubes, String> {
+    if let Some(id) = ids.iter().find(|id| !games.iter().any(|g| g.id == **id)) {
+        return Err(format!("No game {}", id));
+    }
 
-                match data[1].as_str() {
-                    "red" => new.r += cnt,
-                    "green" => new.g += cnt,
-                    "blue" => new.b += cnt,
-                    _ => println!("No such color {}", data[1]),
-                }
+    Ok(min_bag(games.iter().filter(|g| ids.contains(&g.id))))
+}
+
+/* Bag of at most budget cubes admitting the most games, only
+the counts found in the game minimums are worth trying for each
+colour and the last colour takes whatever budget is left */
+fn best_bag(games: &[Game], budget: i32) -> Result<(Cubes, usize), String> {
+    if budget < 0 {
+        return Err(format!("Negative budget {}", budget));
+    }
+
+    let mins: Vec<Cubes> = games.iter().map(|g| g.min()).collect();
+
+    let mut colours: Vec<String> = mins.iter().flat_map(|m| m.keys().cloned()).collect();
+    colours.sort();
+    colours.dedup();
+
+    let candidates: Vec<Vec<i32>> = colours
+        .iter()
+        .map(|c| {
+            let mut v: Vec<i32> = mins.iter().map(|m| *m.get(c).unwrap_or(&0)).collect();
+            v.push(0);
+            v.sort();
+            v.dedup();
+            v
+        })
+        .collect();
+
+    let mut best: (Vec<i32>, usize) = (vec![0; colours.len()], 0);
+    let mut cur: Vec<i32> = Vec::new();
+
+    fn explore(
+        mins: &[Cubes],
+        colours: &[String],
+        candidates: &[Vec<i32>],
+        left: i32,
+        cur: &mut Vec<i32>,
+        best: &mut (Vec<i32>, usize),
+    ) {
+        let x = cur.len();
+
+        if x + 1 >= colours.len() {
+            if x < colours.len() {
+                cur.push(left);
             }
 
-            println!("Add R {} G {} B {}", new.r, new.g, new.b);
+            let admitted = mins
+                .iter()
+                .filter(|m| {
+                    colours
+                        .iter()
+                        .zip(cur.iter())
+                        .all(|(c, v)| *m.get(c).unwrap_or(&0) <= *v)
+                })
+                .count();
+
+            if admitted > best.1 {
+                *best = (cur.clone(), admitted);
+            }
 
-            self.views.push(new);
+            if x < colours.len() {
+                cur.pop();
+            }
+            return;
         }
 
-        Ok(())
+        for v in candidates[x].iter().filter(|v| **v <= left) {
+            cur.push(*v);
+            explore(mins, colours, candidates, left - v, cur, best);
+            cur.pop();
+        }
     }
+
+    explore(&mins, &colours, &candidates, budget, &mut cur, &mut best);
+
+    let bag: Cubes = colours.into_iter().zip(best.0).collect();
+
+    /* Tighten to exactly what the admitted games need */
+    let admitted: Vec<&Game> = games.iter().filter(|g| g.possible(&bag)).collect();
+    Ok((min_bag(admitted.iter().copied()), admitted.len()))
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let file = File::open("./data.txt")?;
-    let mut buf_reader = BufReader::new(file);
-    let mut contents = String::new();
-    buf_reader.read_to_string(&mut contents)?;
+    /* d2 [--bag "12 red, 13 green, 14 blue"] [--explain] [--min-bag 1,2,3] [--budget N] */
+    let mut bag = parse_cubes(&mut Parser::new("12 red, 13 green, 14 blue"))?;
+    let mut explain = false;
+    let mut min_ids: Option<Vec<i32>> = None;
+    let mut budget: Option<i32> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--bag" => {
+                let arg = args.next().ok_or("--bag needs a value")?;
+                bag = parse_cubes(&mut Parser::new(&arg))?;
+            }
+            "--explain" => explain = true,
+            "--min-bag" => {
+                let ids = args.next().ok_or("--min-bag needs game ids")?;
+                let ids: Result<Vec<i32>, _> = ids.split(',').map(|v| v.trim().parse()).collect();
+                min_ids = Some(ids?);
+            }
+            "--budget" => budget = Some(args.next().ok_or("--budget needs a value")?.parse()?),
+            _ => return Err(format!("Unknown argument {}", a).into()),
+        }
+    }
 
     let mut games: Vec<Game> = Vec::new();
 
-    for line in contents.split('\n') {
+    for (x, line) in aoc::input::open("./data.txt")?.lines().enumerate() {
+        let line = line?;
+
         if !line.starts_with("Game") {
             println!("Skipping {}", line);
             continue;
         }
 
-        let colon = line.find(':').unwrap();
-        let id = String::from(&line[5..colon]).parse::<i32>()?;
-        let rounds = String::from(&line[colon + 1..]);
+        let (id, mut rest) = parse::header(&line, "Game").map_err(|e| e.at_line(x + 1))?;
 
         let mut g = Game::new(id);
-        g.push(rounds)?;
+        g.push(&mut rest).map_err(|e| e.at_line(x + 1))?;
 
         games.push(g);
     }
 
+    if explain {
+        for g in games.iter() {
+            for v in g.violations(&bag) {
+                println!(
+                    "Game {} draw {} shows {} {} but the bag has {}",
+                    g.id,
+                    v.draw + 1,
+                    v.seen,
+                    v.colour,
+                    v.available
+                );
+            }
+        }
+    }
+
+    if let Some(ids) = min_ids {
+        let bag = min_bag_of(&games, &ids)?;
+        println!("Minimal bag for games {:?} : {}", ids, fmt_cubes(&bag));
+    }
+
+    if let Some(budget) = budget {
+        let (bag, count) = best_bag(&games, budget)?;
+        println!(
+            "Best bag with {} cubes admits {} games : {}",
+            budget,
+            count,
+            fmt_cubes(&bag)
+        );
+    }
+
     let sumpossible: i32 = games
         .iter()
-        .filter(|v| v.possible(12, 13, 14))
+        .filter(|v| v.possible(&bag))
         .map(|v| v.id)
         .sum();
 
-    let sumpower: i32 = games.iter().map(|v| v.power()).sum();
+    let sumpower: i64 = games.iter().map(|v| v.power(&bag)).sum();
 
-    println!("Sum of possible : {} sumpower : {}", sumpossible, sumpower);
+    aoc::ledger::answer(1, sumpossible);
+    aoc::ledger::answer(2, sumpower);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::rng::Rng;
+
+    fn game(draws: &str) -> Game {
+        let mut g = Game::new(1);
+        g.push(&mut Parser::new(draws)).unwrap();
+        g
+    }
+
+    fn cubes(s: &str) -> Cubes {
+        parse_cubes(&mut Parser::new(s)).unwrap()
+    }
+
+    /* Games of the puzzle example, numbered from 1 */
+    fn example() -> Vec<Game> {
+        [
+            "3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+            "1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+            "6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(x, v)| Game {
+            id: x as i32 + 1,
+            ..game(v)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(cubes("3 blue, 4 red, 2 blue").get("blue"), Some(&5));
+
+        let e = parse_cubes(&mut Parser::new("3 blue,  -4 red")).unwrap_err();
+        assert_eq!(e.to_string(), "col 10: negative count -4");
+    }
+
+    #[test]
+    fn min_bags() {
+        let games = example();
+
+        assert_eq!(
+            min_bag_of(&games, &[1, 2]),
+            Ok(cubes("4 red, 3 green, 6 blue"))
+        );
+        assert_eq!(
+            min_bag_of(&games, &[1, 2, 3, 4, 5]),
+            Ok(cubes("20 red, 13 green, 15 blue"))
+        );
+        assert_eq!(min_bag_of(&games, &[]), Ok(Cubes::new()));
+        assert_eq!(min_bag_of(&games, &[2, 9, 1]), Err("No game 9".to_string()));
+    }
+
+    #[test]
+    fn best_bags() {
+        let games = example();
+
+        assert_eq!(best_bag(&games, 0), Ok((Cubes::new(), 0)));
+        assert_eq!(best_bag(&games, 12).unwrap().1, 1);
+        assert_eq!(
+            best_bag(&games, 13),
+            Ok((cubes("6 red, 3 green, 4 blue"), 2))
+        );
+        assert_eq!(best_bag(&games, 100).unwrap().1, 5);
+        assert_eq!(best_bag(&games, -1), Err("Negative budget -1".to_string()));
+    }
+
+    /* The search admits as many games as the best subset
+    whose minimal bag fits in the budget */
+    #[test]
+    fn best_bag_brute_force() {
+        let colours = ["red", "green", "blue"];
+
+        for seed in 0..100 {
+            let mut rng = Rng::new(seed);
+
+            let games: Vec<Game> = (0..1 + rng.index(6))
+                .map(|x| {
+                    let draws: Vec<String> = (0..1 + rng.index(3))
+                        .map(|_| {
+                            let c = *rng.pick(&colours);
+                            format!("{} {}", rng.below(6), c)
+                        })
+                        .collect();
+                    Game {
+                        id: x as i32 + 1,
+                        ..game(&draws.join("; "))
+                    }
+                })
+                .collect();
+
+            let budget = rng.below(15) as i32;
+            let (bag, count) = best_bag(&games, budget).unwrap();
+
+            let brute = (0..1usize << games.len())
+                .map(|set| {
+                    let picked = games.iter().enumerate().filter(|(x, _)| set >> x & 1 == 1);
+                    let total: i32 = min_bag(picked.map(|(_, g)| g)).values().sum();
+                    (total <= budget).then(|| set.count_ones() as usize)
+                })
+                .max()
+                .flatten()
+                .unwrap();
+
+            assert_eq!(count, brute, "seed {}", seed);
+            assert!(bag.values().sum::<i32>() <= budget, "seed {}", seed);
+            assert_eq!(games.iter().filter(|g| g.possible(&bag)).count(), count);
+        }
+    }
+
+    #[test]
+    fn power() {
+        let bag = parse_cubes(&mut Parser::new("12 red, 13 green, 14 blue")).unwrap();
+
+        assert_eq!(
+            game("3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").power(&bag),
+            48
+        );
+
+        /* No green drawn, no green needed */
+        assert_eq!(game("3 blue, 4 red; 1 red, 6 blue").power(&bag), 0);
+
+        /* Colours outside the bag play no part */
+        assert_eq!(game("1 red, 2 green, 3 blue, 7 pink").power(&bag), 6);
+    }
+}
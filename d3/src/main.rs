@@ -1,221 +1,359 @@
-use std::{
-    error::Error,
-    fs::File,
-    io::{BufRead, BufReader},
-    str::FromStr,
-};
-
-use anyhow::Error as Aerr;
-
-struct Array2D {
-    lines: Vec<Vec<u8>>,
-    w: usize,
-    h: usize,
+use std::{error::Error, fs};
+
+use aoc::grid::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PartNumber {
+    value: u32,
+    row: usize,
+    /* Columns start..end, end excluded */
+    span: (usize, usize),
 }
 
-impl Array2D {
-    fn new() -> Array2D {
-        Array2D {
-            lines: Vec::new(),
-            w: 0,
-            h: 0,
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Symbol {
+    kind: u8,
+    pos: (usize, usize),
+}
 
-    fn add_line(&mut self, data: &String) -> Result<(), Aerr> {
-        if self.w == 0 {
-            self.w = data.len();
-        } else if self.w != data.len() {
-            return Err(Aerr::msg(format!(
-                "Bad line lenght {} != {}\n{}",
-                data.len(),
-                self.w,
-                data
-            )));
-        }
+struct Schematic {
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+    /* For each symbol the numbers touching it */
+    adjacent: Vec<Vec<usize>>,
+}
 
-        let data: Vec<u8> = data.clone().into_bytes();
+impl Schematic {
+    fn new(a: &Grid<u8>) -> Schematic {
+        let mut numbers: Vec<PartNumber> = Vec::new();
+        let mut symbols: Vec<Symbol> = Vec::new();
 
-        self.lines.push(data);
+        /* Number covering each cell, if any */
+        let mut owner: Grid<Option<usize>> = Grid::new(a.rows(), a.cols(), None);
 
-        self.h += 1;
+        for (x, l) in a.rows_iter().enumerate() {
+            let mut y = 0;
+            while y < l.len() {
+                let v = l[y];
 
-        Ok(())
-    }
+                if v.is_ascii_digit() {
+                    let start = y;
+                    let mut value: u32 = 0;
+
+                    while y < l.len() && l[y].is_ascii_digit() {
+                        value = value * 10 + (l[y] - b'0') as u32;
+                        owner[(x, y)] = Some(numbers.len());
+                        y += 1;
+                    }
 
-    fn get_gears(&self) -> Vec<(usize, usize)> {
-        let mut ret: Vec<(usize, usize)> = Vec::new();
+                    numbers.push(PartNumber {
+                        value,
+                        row: x,
+                        span: (start, y),
+                    });
+                    continue;
+                }
 
-        for (x, l) in self.lines.iter().enumerate() {
-            for (y, v) in l.iter().enumerate() {
-                if *v == b'*' {
-                    ret.push((x, y));
+                if v != b'.' {
+                    symbols.push(Symbol {
+                        kind: v,
+                        pos: (x, y),
+                    });
                 }
+
+                y += 1;
             }
         }
 
-        ret
+        let adjacent = symbols
+            .iter()
+            .map(|s| {
+                let mut near: Vec<usize> =
+                    owner.neighbours8(s.pos).filter_map(|p| owner[p]).collect();
+
+                /* Numbers are indexed in reading order */
+                near.sort();
+                near.dedup();
+                near
+            })
+            .collect();
+
+        Schematic {
+            numbers,
+            symbols,
+            adjacent,
+        }
     }
 
-    fn get_locators_close_to(
-        locators: &[(usize, usize, usize)],
-        coord: &(usize, usize),
-    ) -> Vec<(usize, usize, usize)> {
-        let mut ret: Vec<(usize, usize, usize)> = Vec::new();
-
-        for loc in locators.iter() {
-            for dx in -1..2 {
-                for dy in -1..2 {
-                    if (((coord.0 as i32 + dx) as usize) == loc.0)
-                        && (loc.1 as i32 <= (coord.1 as i32 + dy))
-                        && ((coord.1 as i32 + dy) < (loc.1 + loc.2) as i32)
-                    {
-                        ret.push(*loc);
-                        break;
-                    }
-                }
+    /* Numbers touching at least one symbol */
+    fn part_numbers(&self) -> Vec<&PartNumber> {
+        let mut is_part = vec![false; self.numbers.len()];
+
+        for near in self.adjacent.iter() {
+            for n in near.iter() {
+                is_part[*n] = true;
             }
         }
 
-        ret
+        self.numbers
+            .iter()
+            .zip(is_part)
+            .filter(|(_, p)| *p)
+            .map(|(n, _)| n)
+            .collect()
     }
 
-    fn get_at(&self, x: usize, y: usize) -> Option<u8> {
-        if let Some(line) = self.lines.get(x) {
-            if let Some(val) = line.get(y) {
-                return Some(*val);
-            }
-        }
-        None
+    /* Symbols of the given kind touching exactly count numbers */
+    fn gears(&self, kind: u8, count: usize) -> Vec<(&Symbol, Vec<&PartNumber>)> {
+        self.symbols
+            .iter()
+            .zip(self.adjacent.iter())
+            .filter(|(s, near)| s.kind == kind && near.len() == count)
+            .map(|(s, near)| (s, near.iter().map(|n| &self.numbers[*n]).collect()))
+            .collect()
     }
 
-    fn get_numbers_locators(&self) -> Vec<(usize, usize, usize)> {
-        let mut ret: Vec<(usize, usize, usize)> = Vec::new();
+    /* Schematic with counted numbers in green, ignored ones in red
+    and the selected gears in bold yellow */
+    fn render(&self, a: &Grid<u8>, kind: u8, count: usize) -> String {
+        const PART: &str = "\x1b[32m";
+        const IGNORED: &str = "\x1b[31m";
+        const GEAR: &str = "\x1b[1;33m";
+        const RESET: &str = "\x1b[0m";
 
-        for (x, l) in self.lines.iter().enumerate() {
-            let mut y = 0;
-            while y < self.w {
-                let v = l[y];
-                if v.is_ascii_digit() {
-                    let mut vlen = 1;
-                    /* Get value length */
-                    let mut yy = y + 1;
-                    while let Some(vv) = l.get(yy) {
-                        if vv.is_ascii_digit() {
-                            vlen += 1;
-                        } else {
-                            break;
-                        }
-                        yy += 1;
-                    }
-                    ret.push((x, y, vlen));
-                    /* Skip found number */
-                    y += vlen;
-                }
-                y += 1;
-            }
+        let mut style: Grid<Option<&str>> = Grid::new(a.rows(), a.cols(), None);
+
+        for n in self.numbers.iter() {
+            style.row_mut(n.row)[n.span.0..n.span.1].fill(Some(IGNORED));
         }
 
-        ret
-    }
+        for n in self.part_numbers() {
+            style.row_mut(n.row)[n.span.0..n.span.1].fill(Some(PART));
+        }
 
-    fn locator_to_number(&self, desc: &(usize, usize, usize)) -> Option<i32> {
-        if let Some(line) = self.lines.get(desc.0) {
-            if let Ok(val) = String::from_utf8(line[desc.1..desc.1 + desc.2].to_vec()) {
-                if let Ok(v) = val.parse::<i32>() {
-                    return Some(v);
-                }
-            }
+        for (g, _) in self.gears(kind, count) {
+            style[g.pos] = Some(GEAR);
         }
 
-        None
-    }
+        let mut ret = String::new();
 
-    fn has_surounding_symbol(&self, x: usize, y: usize) -> bool {
-        for dx in -1..2 {
-            for dy in -1..2 {
-                let xx: i32 = x as i32 + dx;
-                let yy: i32 = y as i32 + dy;
+        for (l, st) in a.rows_iter().zip(style.rows_iter()) {
+            let mut cur: Option<&str> = None;
 
-                if (xx < 0) || (yy < 0) {
-                    continue;
+            for (v, s) in l.iter().zip(st.iter()) {
+                if *s != cur {
+                    ret.push_str(s.unwrap_or(RESET));
+                    cur = *s;
                 }
+                ret.push(*v as char);
+            }
 
-                if let Some(v) = self.get_at(xx as usize, yy as usize) {
-                    if v == b'.' || v.is_ascii_digit() {
-                        continue;
-                    }
-                    return true;
-                }
+            if cur.is_some() {
+                ret.push_str(RESET);
             }
+            ret.push('\n');
         }
 
-        false
+        ret
     }
 
-    fn check_number_has_surrounding(&self, desc: (usize, usize, usize)) -> bool {
-        let x = desc.0;
-        for y in desc.1..(desc.1 + desc.2) {
-            if self.has_surounding_symbol(x, y) {
-                return true;
-            }
+    /* One line per gear with its numbers and ratio */
+    fn report(&self, kind: u8, count: usize) -> String {
+        let mut ret = String::new();
+        let mut total: u64 = 0;
+
+        for (g, near) in self.gears(kind, count) {
+            let values: Vec<String> = near.iter().map(|n| n.value.to_string()).collect();
+            let ratio: u64 = near.iter().map(|n| n.value as u64).product();
+            total += ratio;
+
+            ret.push_str(&format!(
+                "Gear '{}' at {}:{} : {} = {}\n",
+                g.kind as char,
+                g.pos.0,
+                g.pos.1,
+                values.join(" * "),
+                ratio
+            ));
         }
 
-        false
+        ret.push_str(&format!("Total {}\n", total));
+        ret
+    }
+
+    fn gear_ratios(&self, kind: u8, count: usize) -> Vec<u64> {
+        self.gears(kind, count)
+            .iter()
+            .map(|(_, near)| near.iter().map(|n| n.value as u64).product())
+            .collect()
     }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let f = File::open("./data.txt")?;
-    let mut r = BufReader::new(f);
+    /* d3 [--symbol C] [--count N] [--render] [--report FILE]
+    selects which symbols are gears and how to show them */
+    let mut kind = b'*';
+    let mut count = 2;
+    let mut render = false;
+    let mut report: Option<String> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--symbol" => {
+                let s = args.next().ok_or("--symbol needs a character")?;
+                if s.len() != 1 {
+                    return Err(format!("Symbol '{}' must be a single byte", s).into());
+                }
+                kind = s.as_bytes()[0];
+            }
+            "--count" => count = args.next().ok_or("--count needs a value")?.parse()?,
+            "--render" => render = true,
+            "--report" => report = Some(args.next().ok_or("--report needs a file name")?),
+            _ => return Err(format!("Unknown argument {}", a).into()),
+        }
+    }
 
-    let mut data = String::new();
+    let mut r = aoc::input::open("./data.txt")?;
 
-    let mut array = Array2D::new();
+    let mut array: Grid<u8> = Grid::default();
 
-    while let Ok(len) = r.read_line(&mut data) {
-        if len == 0 {
-            break;
-        }
+    while let Some(line) = r.next_line()? {
+        array.push_row(line.as_bytes().to_vec())?;
+    }
 
-        if data.ends_with('\n') {
-            data = String::from(&data[0..data.len() - 1]);
-        }
+    let schematic = Schematic::new(&array);
 
-        array.add_line(&data)?;
+    if render {
+        print!("{}", schematic.render(&array, kind, count));
+    }
 
-        data.clear();
+    if let Some(path) = report {
+        fs::write(path, schematic.report(kind, count))?;
     }
 
-    let nums = array.get_numbers_locators();
+    let sum: u32 = schematic.part_numbers().iter().map(|v| v.value).sum();
 
-    let nums_with_symb: Vec<i32> = nums
-        .iter()
-        .filter(|v| array.check_number_has_surrounding(**v))
-        .filter_map(|v| array.locator_to_number(v))
-        .collect();
+    aoc::ledger::answer(1, sum);
 
-    let sum: i32 = nums_with_symb.iter().sum();
+    let prod_sum: u64 = schematic.gear_ratios(kind, count).iter().sum();
 
-    println!("Sum {:?}", sum);
+    aoc::ledger::answer(2, prod_sum);
 
-    let gears = array.get_gears();
+    Ok(())
+}
 
-    let mut prod_sum = 0;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::rng::Rng;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    fn schematic(text: &str) -> (Grid<u8>, Schematic) {
+        let g = Grid::parse(text).unwrap();
+        let s = Schematic::new(&g);
+        (g, s)
+    }
 
-    for g in gears.iter() {
-        let matches_prod: Vec<i32> = Array2D::get_locators_close_to(&nums, g)
-            .iter()
-            .filter_map(|v| array.locator_to_number(v))
-            .collect();
+    fn values(v: &[&PartNumber]) -> Vec<u32> {
+        v.iter().map(|n| n.value).collect()
+    }
 
-        if matches_prod.len() > 1 {
-            let prod: i32 = matches_prod.iter().product();
-            prod_sum += prod;
-        }
+    #[test]
+    fn example() {
+        let (_, s) = schematic(EXAMPLE);
+
+        assert_eq!(s.numbers.len(), 10);
+        assert_eq!(values(&s.part_numbers()).iter().sum::<u32>(), 4361);
+        assert!(!values(&s.part_numbers()).contains(&114));
+        assert!(!values(&s.part_numbers()).contains(&58));
+
+        assert_eq!(s.gear_ratios(b'*', 2), [16345, 451490]);
+        assert_eq!(s.gear_ratios(b'*', 2).iter().sum::<u64>(), 467835);
+
+        /* The '*' next to 617 alone is no gear, unless one is enough */
+        assert_eq!(s.gear_ratios(b'*', 1), [617]);
+        assert_eq!(s.gear_ratios(b'#', 1), [633]);
+        assert!(s.gear_ratios(b'*', 3).is_empty());
+
+        let report = s.report(b'*', 2);
+        assert!(
+            report.starts_with("Gear '*' at 1:3 : 467 * 35 = 16345\n"),
+            "{}",
+            report
+        );
+        assert!(report.ends_with("Total 467835\n"), "{}", report);
     }
 
-    println!("Prod sum {}", prod_sum);
+    /* Symbols on the first row and column have neighbours on
+    one side only, nothing before them may wrap around */
+    #[test]
+    fn edges() {
+        let (_, s) = schematic("*12\n3..\n...\n..4\n");
+        assert_eq!(values(&s.part_numbers()), [12, 3]);
+        assert_eq!(s.adjacent, [vec![0, 1]]);
+
+        let (_, s) = schematic("1.9\n..#\n7.5\n");
+        assert_eq!(values(&s.part_numbers()), [9, 5]);
+
+        let (_, s) = schematic("5....\n....#\n");
+        assert!(s.part_numbers().is_empty());
+
+        /* Numbers run up to the end of their row only */
+        let (_, s) = schematic("..12\n34*.\n");
+        assert_eq!(values(&s.part_numbers()), [12, 34]);
+        assert_eq!(s.numbers[0].span, (2, 4));
+        assert_eq!(s.numbers[1].span, (0, 2));
+        assert_eq!(s.gear_ratios(b'*', 2), [408]);
+    }
 
-    Ok(())
+    /* A symbol touches a number when one of its digits is at
+    most one row and one column away */
+    #[test]
+    fn adjacency_brute_force() {
+        for seed in 0..100 {
+            let mut rng = Rng::new(seed);
+            let (rows, cols) = (1 + rng.index(8), 1 + rng.index(8));
+
+            let text: String = (0..rows)
+                .map(|_| {
+                    let row: String = (0..cols)
+                        .map(|_| match rng.below(10) {
+                            0..=4 => char::from(b'0' + rng.below(10) as u8),
+                            5 => *rng.pick(&['*', '#', '$']),
+                            _ => '.',
+                        })
+                        .collect();
+                    row + "\n"
+                })
+                .collect();
+
+            let (_, s) = schematic(&text);
+
+            for (sym, near) in s.symbols.iter().zip(s.adjacent.iter()) {
+                let want: Vec<usize> = (0..s.numbers.len())
+                    .filter(|x| {
+                        let n = &s.numbers[*x];
+                        let (r, c) = (sym.pos.0 as i64, sym.pos.1 as i64);
+                        (n.row as i64 - r).abs() <= 1
+                            && (n.span.0 as i64) <= c + 1
+                            && (n.span.1 as i64 - 1) >= c - 1
+                    })
+                    .collect();
+
+                assert_eq!(*near, want, "seed {} symbol {:?}\n{}", seed, sym.pos, text);
+            }
+        }
+    }
 }
@@ -1,9 +1,9 @@
 use std::error::Error;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 
 use std::collections::HashSet;
 
+use aoc::parse::{self, ParseError};
+
 #[derive(Debug, Clone)]
 struct Card {
     id: i32,
@@ -13,32 +13,13 @@ struct Card {
 }
 
 impl Card {
-    fn parse_num_list(data: &str) -> Vec<i32> {
-        let mut ret: Vec<i32> = Vec::new();
-
-        for x in data.split(' ') {
-            if let Ok(v) = x.trim().parse::<i32>() {
-                ret.push(v);
-            }
-        }
-
-        ret
-    }
-
-    fn new(data: &str) -> Card {
-        assert!(data.starts_with("Card "));
-
-        let colon = data.find(':').unwrap();
-
-        let series = String::from(&data[colon + 1..]);
-        let id = String::from(&data[5..colon]).trim().parse::<i32>().unwrap();
+    /* "Card 1: 41 48 83 | 83 86  6 31" */
+    fn new(data: &str) -> Result<Card, ParseError> {
+        let (id, mut series) = parse::header(data, "Card")?;
+        let (mut ent, mut win) = series.split_once("|")?;
 
-        let ent: Vec<&str> = series.split('|').collect();
-
-        assert!(ent.len() == 2);
-
-        let candi = Card::parse_num_list(ent[0]);
-        let winning = Card::parse_num_list(ent[1]);
+        let candi: Vec<i32> = ent.numbers()?;
+        let winning: Vec<i32> = win.numbers()?;
 
         let mut ret = Card {
             id,
@@ -59,7 +40,7 @@ impl Card {
 
         //println!("{:?} {} {}", ret, ret.score(), ret.matches);
 
-        ret
+        Ok(ret)
     }
 
     fn _num_matching(&self) -> i32 {
@@ -105,27 +86,15 @@ fn unfold_winning_cards(cards: &Vec<Card>, card: &Card) -> i32 {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let f = File::open("data.txt")?;
-
-    let mut r = BufReader::new(f);
-
     let mut cards: Vec<Card> = Vec::new();
 
-    let mut data = String::new();
-
-    while let Ok(l) = r.read_line(&mut data) {
-        if l == 0 {
-            break;
-        }
-
-        cards.push(Card::new(&data));
-
-        data.clear();
+    for (x, line) in aoc::input::open("data.txt")?.lines().enumerate() {
+        cards.push(Card::new(&line?).map_err(|e| e.at_line(x + 1))?);
     }
 
     let total: i32 = cards.iter().map(|v| v.score()).sum();
 
-    println!("Total score : {}", total);
+    aoc::ledger::answer(1, total);
 
     /* Now I process with Part 2 rule */
     let mut ret = 0;
@@ -134,7 +103,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         ret += unfold_winning_cards(&cards, c);
     }
 
-    println!("Final stack len {}", ret);
+    aoc::ledger::answer(2, ret);
 
     Ok(())
 }
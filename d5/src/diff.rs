@@ -0,0 +1,98 @@
+use aoc::diff::Differential;
+use aoc::rng::Rng;
+
+use crate::{gen, Almanac};
+
+/* Mapping whole seed ranges against mapping their seeds one by one */
+pub struct Ranges {
+    pub ranges: u64,
+    pub pairs: u64,
+    pub span: u64,
+    pub max: u64,
+}
+
+fn load(data: &str) -> Almanac {
+    let fields: Vec<Vec<String>> = aoc::input::LineReader::new(data.as_bytes())
+        .blocks()
+        .map(|b| b.unwrap())
+        .collect();
+
+    Almanac::new(&fields).unwrap()
+}
+
+/* data with its line x replaced, None drops it */
+fn with_line(lines: &[&str], x: usize, l: Option<String>) -> String {
+    let mut ret = String::new();
+
+    for (y, v) in lines.iter().enumerate() {
+        if y != x {
+            ret.push_str(v);
+            ret.push('\n');
+        } else if let Some(l) = &l {
+            ret.push_str(l);
+            ret.push('\n');
+        }
+    }
+
+    ret
+}
+
+fn join(n: &[i64]) -> String {
+    let n: Vec<String> = n.iter().map(|v| v.to_string()).collect();
+    n.join(" ")
+}
+
+impl Differential for Ranges {
+    type Input = String;
+    type Output = Option<i64>;
+
+    fn generate(&self, rng: &mut Rng) -> String {
+        gen::almanac(rng, self.ranges, self.pairs, self.span, self.max)
+    }
+
+    fn shrink(&self, data: &String) -> Vec<String> {
+        let mut ret = Vec::new();
+        let lines: Vec<&str> = data.lines().collect();
+        let seeds = load(data).seeds;
+
+        /* One seed pair less, or a shorter one */
+        for x in (0..seeds.len()).step_by(2) {
+            if seeds.len() > 2 {
+                let mut s = seeds.clone();
+                s.drain(x..x + 2);
+                ret.push(with_line(&lines, 0, Some(format!("seeds: {}", join(&s)))));
+            }
+
+            if seeds[x + 1] > 1 {
+                let mut s = seeds.clone();
+                s[x + 1] /= 2;
+                ret.push(with_line(&lines, 0, Some(format!("seeds: {}", join(&s)))));
+            }
+        }
+
+        /* One map entry less, or a shorter one */
+        for (x, l) in lines.iter().enumerate().skip(1) {
+            let n: Vec<i64> = match aoc::parse::numbers(l) {
+                Ok(n) if n.len() == 3 => n,
+                _ => continue,
+            };
+
+            ret.push(with_line(&lines, x, None));
+
+            if n[2] > 1 {
+                let l = format!("{} {} {}", n[0], n[1], n[2] / 2);
+                ret.push(with_line(&lines, x, Some(l)));
+            }
+        }
+
+        ret
+    }
+
+    fn fast(&self, data: &String) -> Option<i64> {
+        load(data).min_by_ranges()
+    }
+
+    fn reference(&self, data: &String) -> Option<i64> {
+        load(data).min_per_seed()
+    }
+}
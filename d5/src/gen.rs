@@ -0,0 +1,49 @@
+use aoc::rng::Rng;
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/* Almanac with `pairs` seed ranges of at most `span` seeds and `ranges`
+lines per map, all values stay under max. A quarter of the source ranges
+start inside the previous one so overlaps are common, the first listed wins */
+pub fn almanac(rng: &mut Rng, ranges: u64, pairs: u64, span: u64, max: u64) -> String {
+    assert!(span > 0 && span < max, "span must be in 1..max");
+
+    let mut ret = String::from("seeds:");
+
+    for _ in 0..pairs {
+        let len = 1 + rng.below(span);
+        let start = rng.below(max - len + 1);
+        ret.push_str(&format!(" {} {}", start, len));
+    }
+    ret.push('\n');
+
+    for w in CATEGORIES.windows(2) {
+        ret.push_str(&format!("\n{}-to-{} map:\n", w[0], w[1]));
+
+        let mut prev: Option<(u64, u64)> = None;
+
+        for _ in 0..ranges {
+            let len = 1 + rng.below((max / 4).max(1));
+
+            let source = match prev {
+                Some((s, l)) if rng.chance(25) => (s + rng.below(l)).min(max - len),
+                _ => rng.below(max - len + 1),
+            };
+            let dest = rng.below(max - len + 1);
+
+            ret.push_str(&format!("{} {} {}\n", dest, source, len));
+            prev = Some((source, len));
+        }
+    }
+
+    ret
+}
@@ -1,19 +1,15 @@
 use rayon::prelude::*;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, Read};
 
-fn load_num_list(data: &str) -> Vec<i64> {
-    let mut ret: Vec<i64> = Vec::new();
+use aoc::args::Args;
+use aoc::parse::{self, ParseError, Parser};
+use aoc::rng::Rng;
 
-    for v in data.trim().split(' ') {
-        if let Ok(v) = v.parse::<i64>() {
-            ret.push(v);
-        }
-    }
+mod diff;
+mod gen;
 
-    ret
-}
+/* Values from start up to end excluded */
+type Span = (i64, i64);
 
 struct LookupEntry {
     source: i64,
@@ -33,6 +29,25 @@ impl LookupEntry {
 
         None
     }
+
+    /* The part of the seeds range [start, end) this entry maps,
+    already mapped, and the parts it leaves alone */
+    fn split(&self, r: Span) -> (Option<Span>, Vec<Span>) {
+        let start = r.0.max(self.source);
+        let end = r.1.min(self.source + self.len);
+
+        if start >= end {
+            return (None, vec![r]);
+        }
+
+        let shift = self.dest - self.source;
+        let rest = [(r.0, start), (end, r.1)]
+            .into_iter()
+            .filter(|(s, e)| s < e)
+            .collect();
+
+        (Some((start + shift, end + shift)), rest)
+    }
 }
 
 struct Lookup {
@@ -42,28 +57,30 @@ struct Lookup {
 }
 
 impl Lookup {
-    fn new(data: &str) -> Lookup {
-        let lines: Vec<&str> = data.split('\n').collect();
-
-        assert!(lines[0].contains(" map:"));
+    /* "seed-to-soil map:" then one "dest source len" line per range */
+    fn new(lines: &[String]) -> Result<Lookup, ParseError> {
+        let mut p = Parser::new(&lines[0]);
 
-        let set_desc = lines[0][..lines[0].len() - " map:".len()].to_string();
-        let set_desc: Vec<&str> = set_desc.split("-to-").collect();
-        assert!(set_desc.len() == 2);
-
-        let from = set_desc[0].trim().to_string();
-        let to = set_desc[1].trim().to_string();
+        let from = p.word()?.to_string();
+        p.tag("-to-")?;
+        let to = p.word()?.to_string();
+        p.tag("map:")?;
+        p.end()?;
 
         let mut lookups: Vec<LookupEntry> = Vec::new();
 
         for v in lines[1..].iter() {
-            let vals = load_num_list(v);
-            assert!(vals.len() == 3);
+            let mut p = Parser::new(v);
+
+            let dest = p.number()?;
+            let source = p.number()?;
+            let len = p.number()?;
+            p.end()?;
 
-            lookups.push(LookupEntry::new(vals[0], vals[1], vals[2]));
+            lookups.push(LookupEntry::new(dest, source, len));
         }
 
-        Lookup { from, to, lookups }
+        Ok(Lookup { from, to, lookups })
     }
 
     fn resolve(&self, from: &i64) -> i64 {
@@ -75,38 +92,155 @@ impl Lookup {
 
         *from
     }
+
+    /* Same as resolve for every value of the ranges at once,
+    the first entry covering a value still wins */
+    fn resolve_ranges(&self, ranges: Vec<Span>) -> Vec<Span> {
+        let mut pending = ranges;
+        let mut ret: Vec<Span> = Vec::new();
+
+        for l in self.lookups.iter() {
+            let mut left: Vec<Span> = Vec::new();
+
+            for r in pending {
+                let (mapped, rest) = l.split(r);
+                ret.extend(mapped);
+                left.extend(rest);
+            }
+
+            pending = left;
+        }
+
+        ret.extend(pending);
+        ret
+    }
+}
+
+fn get_seeds(data: &str) -> Result<Vec<i64>, ParseError> {
+    parse::labelled(data, "seeds")?.numbers()
 }
 
-fn get_seeds(data: &str) -> Vec<i64> {
-    assert!(data.starts_with("seeds:"));
+struct Almanac {
+    seeds: Vec<i64>,
+    converters: HashMap<String, Lookup>,
+}
+
+impl Almanac {
+    /* Seeds line then one block per map */
+    fn new(fields: &[Vec<String>]) -> Result<Almanac, ParseError> {
+        let seeds = get_seeds(&fields[0][0])?;
+
+        if !seeds.len().is_multiple_of(2) {
+            return Err(ParseError {
+                line: Some(1),
+                col: 1,
+                msg: format!("seeds come in pairs, got {}", seeds.len()),
+            });
+        }
+
+        let mut converters: HashMap<String, Lookup> = HashMap::new();
+
+        for e in fields[1..].iter() {
+            let look = Lookup::new(e)?;
+            converters.insert(look.from.to_string(), look);
+        }
+
+        Ok(Almanac { seeds, converters })
+    }
+
+    fn location(&self, seed: i64) -> i64 {
+        let mut value = seed;
+        let mut current_target = "seed";
+
+        while let Some(lk) = self.converters.get(current_target) {
+            value = lk.resolve(&value);
+            current_target = lk.to.as_str();
+        }
 
-    let colon = data.find(':').unwrap();
+        value
+    }
 
-    let numlist = data[colon + 1..].to_string();
+    fn seed_ranges(&self) -> Vec<Span> {
+        self.seeds.chunks(2).map(|v| (v[0], v[0] + v[1])).collect()
+    }
 
-    load_num_list(&numlist)
+    /* Part 2 one seed at a time */
+    fn min_per_seed(&self) -> Option<i64> {
+        self.seed_ranges()
+            .into_iter()
+            .filter_map(|(start, end)| {
+                (start..end)
+                    .into_par_iter()
+                    .map(|seed| self.location(seed))
+                    .min()
+            })
+            .min()
+    }
+
+    /* Part 2 moving whole ranges through the maps */
+    fn min_by_ranges(&self) -> Option<i64> {
+        let mut ranges = self.seed_ranges();
+        let mut current_target = "seed";
+
+        while let Some(lk) = self.converters.get(current_target) {
+            ranges = lk.resolve_ranges(ranges);
+            current_target = lk.to.as_str();
+        }
+
+        ranges.iter().map(|r| r.0).min()
+    }
 }
 
 fn main() {
-    let f = File::open("data.txt").unwrap();
-    let mut r = BufReader::new(f);
-    let mut data = String::new();
-    r.read_to_string(&mut data).unwrap();
+    /* d5 --gen SEED [RANGES] [PAIRS] [SPAN] [MAX] prints a random almanac */
+    if let Some(a) = Args::get("--gen") {
+        let mut rng = Rng::new(a.need(0, "a seed"));
+        print!(
+            "{}",
+            gen::almanac(
+                &mut rng,
+                a.num(1, 10),
+                a.num(2, 5),
+                a.num(3, 1000),
+                a.num(4, 1 << 32)
+            )
+        );
+        return;
+    }
 
-    let fields: Vec<&str> = data.split("\n\n").collect();
+    /* d5 --diff [RUNS] [RANGES] [PAIRS] [SPAN] [MAX] checks the range
+    mapping against mapping every seed of the ranges */
+    if let Some(a) = Args::get("--diff") {
+        let d = diff::Ranges {
+            ranges: a.num(1, 4),
+            pairs: a.num(2, 3),
+            span: a.num(3, 50),
+            max: a.num(4, 200),
+        };
+        aoc::diff::run(&d, a.num(0, 100));
+        return;
+    }
 
-    assert!(fields.len() > 1);
+    let fields: Vec<Vec<String>> = aoc::input::open("data.txt")
+        .unwrap()
+        .blocks()
+        .map(|b| b.unwrap())
+        .collect();
 
-    let mut converters: HashMap<String, Lookup> = HashMap::new();
+    assert!(fields.len() > 1);
 
-    for e in fields[1..].iter() {
-        let look = Lookup::new(e);
-        converters.insert(look.from.to_string(), look);
-    }
+    let almanac = match Almanac::new(&fields) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("Bad almanac at {}", e);
+            std::process::exit(1);
+        }
+    };
+    let converters = &almanac.converters;
 
     /* Part 1 */
 
-    let mut values = get_seeds(fields[0]);
+    let mut values = almanac.seeds.clone();
     println!("{:?}", values);
 
     let mut current_target = "seed";
@@ -119,40 +253,15 @@ fn main() {
         current_target = lk.to.as_str();
     }
 
-    println!("Part 1 min is {}", values.iter().min().unwrap());
-
-    /* Part 2 */
-
-    let values = get_seeds(fields[0]);
-
-    assert!(values.len() % 2 == 0);
+    aoc::ledger::answer(1, values.iter().min().unwrap());
 
-    let mut k = 0;
+    /* Part 2, d5 --brute checks every seed instead of whole ranges */
 
-    let mut min = 0;
-
-    while k < values.len() {
-        min = (values[k]..values[k + 1] + values[k])
-            .into_par_iter()
-            .map(|seed| {
-                //println!("s! {}", seed);
-                let mut values = vec![seed];
-                let mut current_target = "seed";
-
-                while let Some(lk) = converters.get(current_target) {
-                    //println!("{} -> {}", lk.from, lk.to);
-                    values = values.iter().map(|v| lk.resolve(v)).collect();
-                    current_target = lk.to.as_str();
-                }
-
-                values[0]
-            })
-            .min()
-            .unwrap();
-        println!("Seed {} done", values[k]);
-
-        k += 2;
-    }
+    let min = if std::env::args().any(|a| a == "--brute") {
+        almanac.min_per_seed()
+    } else {
+        almanac.min_by_ranges()
+    };
 
-    println!("Part 2 min is {}", min);
+    aoc::ledger::answer(2, min.unwrap());
 }
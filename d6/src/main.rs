@@ -1,6 +1,9 @@
 use std::fs::File;
 use std::io::{BufReader, Read};
 
+use aoc::math;
+use aoc::parse::{self, ParseError};
+
 struct Run {
     record: i64,
     dist: i64,
@@ -11,51 +14,52 @@ impl Run {
         Run { record, dist }
     }
 
+    /* Holding h goes h * (record - h), over dist between the
+    roots of h^2 - record * h + dist, symmetric around record / 2 */
     fn numpos(&self) -> i64 {
-        let mut ret = 0;
+        let (t, d) = (self.record, self.dist);
 
-        for h in 1..self.record {
-            if h * (self.record - h) > self.dist {
-                //println!("Hold {} works", h);
-                ret += 1;
-            }
+        let disc = t * t - 4 * d;
+        if disc < 0 {
+            return 0;
+        }
+
+        /* Rounded root, moved up to the first winning hold */
+        let mut lo = ((t - math::isqrt(disc as u64) as i64) / 2).max(1);
+        while lo <= t / 2 && lo * (t - lo) <= d {
+            lo += 1;
+        }
+
+        if lo > t / 2 {
+            return 0;
         }
 
-        ret
+        t - lo - lo + 1
     }
 }
 
-fn parse_num_list(data: &str) -> Vec<i64> {
-    let mut ret: Vec<i64> = Vec::new();
+/* "Time:  7  15" numbers, read as one number when merging spaces */
+fn parse_num_list(line: &str, label: &str, merge_spaces: bool) -> Result<Vec<i64>, ParseError> {
+    let mut p = parse::labelled(line, label)?;
 
-    for v in data.split(' ') {
-        if let Ok(v) = v.trim().parse::<i64>() {
-            ret.push(v);
-        }
+    if merge_spaces {
+        return parse::numbers(&p.rest().replace(' ', ""));
     }
 
-    ret
+    p.numbers()
 }
 
 fn load_data(data: &str, merge_spaces: bool) -> (Vec<i64>, Vec<i64>) {
-    let lines: Vec<&str> = data.split('\n').collect();
+    let lines: Vec<&str> = data.lines().collect();
 
     assert!(lines.len() == 2);
 
-    // Part 1
-    let times = lines[0]["Time:".len()..].to_string();
-    let distances = lines[1]["Distance:".len()..].to_string();
-    let times = times.trim().to_string();
-    let distances = distances.trim().to_string();
-
-    let (times, distances) = if merge_spaces {
-        (times.replace(' ', ""), distances.replace(' ', ""))
-    } else {
-        (times, distances)
-    };
-
-    let times = parse_num_list(times.as_str());
-    let distances = parse_num_list(distances.as_str());
+    let times = parse_num_list(lines[0], "Time", merge_spaces)
+        .map_err(|e| e.at_line(1))
+        .unwrap();
+    let distances = parse_num_list(lines[1], "Distance", merge_spaces)
+        .map_err(|e| e.at_line(2))
+        .unwrap();
 
     assert!(times.len() == distances.len());
 
@@ -80,7 +84,7 @@ fn main() {
 
     let prod: i64 = runs.iter().map(|v| v.numpos()).product();
 
-    println!("PART 1 : {}", prod);
+    aoc::ledger::answer(1, prod);
 
     // Part 2
     let (times, distances) = load_data(&data, true);
@@ -93,5 +97,34 @@ fn main() {
 
     let prod: i64 = runs.iter().map(|v| v.numpos()).product();
 
-    println!("PART 2 : {}", prod);
+    aoc::ledger::answer(2, prod);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::rng::Rng;
+
+    fn brute(t: i64, d: i64) -> i64 {
+        (0..=t).filter(|h| h * (t - h) > d).count() as i64
+    }
+
+    #[test]
+    fn numpos_brute_force() {
+        let mut rng = Rng::new(6);
+
+        for _ in 0..5000 {
+            let t = rng.range(0..300);
+            /* Around the best distance t^2 / 4 where the edge cases are */
+            let d = rng.range(0..t * t / 4 + 5);
+
+            assert_eq!(Run::new(t, d).numpos(), brute(t, d), "t {} d {}", t, d);
+        }
+
+        for t in 0..60 {
+            for d in 0..t * t / 4 + 2 {
+                assert_eq!(Run::new(t, d).numpos(), brute(t, d), "t {} d {}", t, d);
+            }
+        }
+    }
 }
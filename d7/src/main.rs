@@ -1,15 +1,11 @@
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-use std::fs::File;
-use std::io::BufReader;
-use std::io::Read;
+use aoc::parse::{ParseError, Parser};
 
 #[derive(Debug, Clone)]
 struct Hand {
     hand: String,
-    score: u64,
     count: HashMap<u8, u32>,
     joker: bool,
 }
@@ -43,18 +39,43 @@ enum Card {
     A,
 }
 
-impl Hand {
-    fn new(data: &str, joker: bool) -> Hand {
-        let v: Vec<&str> = data.split(' ').collect();
+/* A line as read, kept small as every hand of the input is
+held for sorting, the Hand only lives while it is ranked */
+#[derive(Debug, Clone, Copy)]
+struct Deal {
+    cards: [u8; 5],
+    bid: u64,
+}
 
-        assert!(v.len() == 2);
+impl Deal {
+    /* "32T3K 765" */
+    fn parse(data: &str) -> Result<Deal, ParseError> {
+        let mut p = Parser::new(data);
+
+        let hand = p.token()?;
+        let cards: [u8; 5] = hand
+            .as_bytes()
+            .try_into()
+            .ok()
+            .filter(|c: &[u8; 5]| c.iter().all(|v| b"23456789TJQKA".contains(v)))
+            .ok_or_else(|| p.error(format!("{} is not a hand of 5 cards", hand)))?;
+        let bid = p.number()?;
+        p.end()?;
+
+        Ok(Deal { cards, bid })
+    }
 
-        let hand = v[0].to_string();
-        let score = v[1].trim().parse::<u64>().unwrap();
+    fn hand(&self) -> &str {
+        std::str::from_utf8(&self.cards).unwrap()
+    }
+}
+
+impl Hand {
+    fn new(hand: &str, joker: bool) -> Hand {
+        let hand = hand.to_string();
 
         let mut ret = Hand {
             hand,
-            score,
             count: HashMap::new(),
             joker,
         };
@@ -171,97 +192,46 @@ impl Hand {
         }
     }
 
-    fn is_card_lower(&self, other: &Hand) -> bool {
-        //println!("{} && {}", self.hand, other.hand);
-        assert!(self.joker == other.joker);
-
-        let other = other.hand.as_bytes();
-
-        for (i, c) in self.hand.as_bytes().iter().enumerate() {
-            let loc = self.to_card(c);
-            let rem = self.to_card(&other[i]);
-            if loc == rem {
-                //println!("{:?} == {:?}", loc, rem);
-                continue;
-            }
-
-            if loc < rem {
-                //println!("{:?} < {:?}", loc, rem);
-                return true;
-            } else {
-                //println!("{:?} > {:?}", loc, rem);
-
-                return false;
-            }
-        }
-
-        false
+    /* Kind then each card in 4 bits, ordered as the hands rank */
+    fn strength(&self) -> u32 {
+        self.hand.bytes().fold(self.to_kind() as u32, |acc, c| {
+            (acc << 4) | self.to_card(&c) as u32
+        })
     }
 }
 
-fn main() {
-    let f = File::open("data.txt").unwrap();
-    let mut r = BufReader::new(f);
-    let mut data = String::new();
-
-    r.read_to_string(&mut data).unwrap();
+/* Total of the bids times the rank of their hand */
+fn winnings(deals: &[Deal], joker: bool) -> u64 {
+    let mut ranked: Vec<(u32, usize)> = deals
+        .iter()
+        .enumerate()
+        .map(|(x, d)| (Hand::new(d.hand(), joker).strength(), x))
+        .collect();
 
-    let mut hands: Vec<Hand> = Vec::new();
-
-    for v in data.split('\n') {
-        hands.push(Hand::new(v, false));
-    }
-
-    hands.sort_by(|a, b| {
-        let ka = a.to_kind();
-        let kb = b.to_kind();
-
-        if ka == kb {
-            let alower = a.is_card_lower(b);
-            match alower {
-                true => Ordering::Less,
-                false => Ordering::Greater,
-            }
-        } else {
-            ka.cmp(&kb)
-        }
-    });
+    ranked.sort_unstable();
 
     let mut score: u64 = 0;
 
-    for (i, j) in hands.iter().enumerate() {
-        println!("{} = {}", i, j.hand);
-        score += (i as u64 + 1) * j.score;
-    }
-
-    println!("Part 1 Score {}", score);
-
-    hands.clear();
-    for v in data.split('\n') {
-        hands.push(Hand::new(v, true));
+    for (i, (_, x)) in ranked.iter().enumerate() {
+        println!("{} = {}", i, deals[*x].hand());
+        score += (i as u64 + 1) * deals[*x].bid;
     }
 
-    hands.sort_by(|a, b| {
-        let ka = a.to_kind();
-        let kb = b.to_kind();
-
-        if ka == kb {
-            let alower = a.is_card_lower(b);
-            match alower {
-                true => Ordering::Less,
-                false => Ordering::Greater,
-            }
-        } else {
-            ka.cmp(&kb)
-        }
-    });
-
-    let mut score: u64 = 0;
-
-    for (i, j) in hands.iter().enumerate() {
-        println!("{} = {} {:?}", i, j.hand, j.to_kind());
-        score += (i as u64 + 1) * j.score;
-    }
+    score
+}
 
-    println!("Part 2 Score {}", score);
+fn main() {
+    let deals: Vec<Deal> = aoc::input::open("data.txt")
+        .unwrap()
+        .lines()
+        .enumerate()
+        .map(|(x, l)| {
+            Deal::parse(&l.unwrap())
+                .map_err(|e| e.at_line(x + 1))
+                .unwrap()
+        })
+        .collect();
+
+    aoc::ledger::answer(1, winnings(&deals, false));
+    aoc::ledger::answer(2, winnings(&deals, true));
 }
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use aoc::diff::Differential;
+use aoc::rng::Rng;
+
+use crate::{gen, load};
+
+/* Answers past this many steps are too slow to walk, both sides give None */
+const LIMIT: u64 = 10_000;
+
+/* The LCM shortcut against walking every ghost at once */
+pub struct Ghosts {
+    pub ghosts: u64,
+    pub path: u64,
+    pub mult: u64,
+    pub skew: u64,
+}
+
+/* Every node name, in the order of the lines */
+fn names(data: &str) -> Vec<&str> {
+    data.lines()
+        .skip(2)
+        .filter_map(|l| l.split_once(" = "))
+        .map(|(n, _)| n)
+        .collect()
+}
+
+impl Differential for Ghosts {
+    type Input = String;
+    type Output = Option<u64>;
+
+    fn generate(&self, rng: &mut Rng) -> String {
+        gen::network(rng, self.ghosts, self.path, self.mult, self.skew)
+    }
+
+    fn shrink(&self, data: &String) -> Vec<String> {
+        let mut ret = Vec::new();
+        let names = names(data);
+        let used: HashSet<&str> = names.iter().copied().collect();
+
+        /* Nodes nobody else can get to */
+        let targets: HashSet<&str> = data
+            .lines()
+            .skip(2)
+            .filter_map(|l| l.split_once(" = "))
+            .flat_map(|(n, v)| {
+                v.trim_matches(|c| c == '(' || c == ')')
+                    .split(", ")
+                    .filter(move |v| *v != n)
+            })
+            .collect();
+
+        for n in names.iter().filter(|n| !n.ends_with('A')) {
+            if !targets.contains(n) {
+                let lines: Vec<&str> = data
+                    .lines()
+                    .filter(|l| !l.starts_with(&format!("{} =", n)))
+                    .collect();
+                ret.push(lines.join("\n") + "\n");
+            }
+        }
+
+        /* One ghost less, its start node gets renamed */
+        for n in names.iter().filter(|n| n.ends_with('A')) {
+            let fresh = (b'B'..b'Z')
+                .map(|c| format!("{}{}", &n[..2], c as char))
+                .find(|v| !used.contains(v.as_str()));
+
+            if let Some(fresh) = fresh {
+                ret.push(data.replace(n, &fresh));
+            }
+        }
+
+        /* Shorter instructions */
+        let (dirs, rest) = data.split_once('\n').unwrap();
+
+        if dirs.len() > 1 {
+            ret.push(format!("{}\n{}", &dirs[..dirs.len() / 2], rest));
+            ret.push(format!("{}\n{}", &dirs[..dirs.len() - 1], rest));
+        }
+
+        /* A choice turned into a dead loop, which may leave other
+        nodes unreferenced */
+        let lines: Vec<&str> = data.lines().collect();
+
+        for (x, l) in lines.iter().enumerate().skip(2) {
+            let (n, choices) = l.split_once(" = ").unwrap();
+            let (left, right) = choices
+                .trim_matches(|c| c == '(' || c == ')')
+                .split_once(", ")
+                .unwrap();
+
+            for v in [(n, right), (left, n)] {
+                if v != (left, right) {
+                    let mut lines = lines.clone();
+                    let l = format!("{} = ({}, {})", n, v.0, v.1);
+                    lines[x] = &l;
+                    ret.push(lines.join("\n") + "\n");
+                }
+            }
+        }
+
+        ret
+    }
+
+    fn fast(&self, data: &String) -> Option<u64> {
+        let (path, map) = load(data).unwrap();
+
+        map.ghosts_lcm(&path).filter(|v| *v <= LIMIT)
+    }
+
+    fn reference(&self, data: &String) -> Option<u64> {
+        let (path, map) = load(data).unwrap();
+
+        map.ghosts_walk(&path, LIMIT)
+    }
+}
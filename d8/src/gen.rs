@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use aoc::rng::Rng;
+
+const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/* Fresh 3 letters name ending with last, or with anything
+but A and Z when last is None */
+fn name(rng: &mut Rng, used: &mut HashSet<String>, last: Option<u8>) -> String {
+    loop {
+        let end = last.unwrap_or_else(|| *rng.pick(&LETTERS[1..25]));
+        let n = format!(
+            "{}{}{}",
+            *rng.pick(LETTERS) as char,
+            *rng.pick(LETTERS) as char,
+            end as char
+        );
+
+        if used.insert(n.clone()) {
+            return n;
+        }
+    }
+}
+
+/* Network where each of the `ghosts` starting nodes (AAA for the first
+one) reaches its own Z node after path * k steps, k picked in 1..=mult,
+then loops back to the node following its start. The branch not taken
+at each step points to a random node so the map looks tangled.
+With a probability of skew percent a ghost loops back further down its
+chain instead, so its period no longer matches its first Z */
+pub fn network(rng: &mut Rng, ghosts: u64, path: u64, mult: u64, skew: u64) -> String {
+    assert!(ghosts > 0 && path > 0 && mult > 0, "sizes must not be 0");
+    assert!(ghosts < 600, "too many ghosts for 3 letters names");
+
+    let dirs: Vec<u8> = (0..path)
+        .map(|_| if rng.chance(50) { b'L' } else { b'R' })
+        .collect();
+
+    let mut used: HashSet<String> = HashSet::new();
+    let mut chains: Vec<Vec<String>> = Vec::new();
+
+    for g in 0..ghosts {
+        let steps = path * (1 + rng.below(mult));
+
+        let mut chain: Vec<String> = Vec::new();
+
+        if g == 0 {
+            used.insert("AAA".to_string());
+            used.insert("ZZZ".to_string());
+            chain.push("AAA".to_string());
+        } else {
+            chain.push(name(rng, &mut used, Some(b'A')));
+        }
+
+        for _ in 1..steps {
+            chain.push(name(rng, &mut used, None));
+        }
+
+        if g == 0 {
+            chain.push("ZZZ".to_string());
+        } else {
+            chain.push(name(rng, &mut used, Some(b'Z')));
+        }
+
+        chains.push(chain);
+    }
+
+    let all: Vec<&String> = chains.iter().flatten().collect();
+    let mut nodes: Vec<String> = Vec::new();
+
+    for chain in chains.iter() {
+        let steps = chain.len() - 1;
+        let back = if skew > 0 && rng.chance(skew) {
+            1 + path as usize * rng.index(steps / path as usize)
+        } else {
+            1
+        };
+
+        for (x, n) in chain.iter().enumerate() {
+            /* The Z node is left at the first instruction like the start */
+            let next = if x < steps {
+                &chain[x + 1]
+            } else {
+                &chain[back]
+            };
+            let other = *rng.pick(&all);
+
+            let (l, r) = match dirs[x % dirs.len()] {
+                b'L' => (next, other),
+                _ => (other, next),
+            };
+
+            nodes.push(format!("{} = ({}, {})", n, l, r));
+        }
+    }
+
+    rng.shuffle(&mut nodes);
+
+    format!(
+        "{}\n\n{}\n",
+        String::from_utf8(dirs).unwrap(),
+        nodes.join("\n")
+    )
+}
@@ -1,8 +1,13 @@
-use indicatif::ProgressBar;
-use indicatif::ProgressStyle;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::BufRead;
+
+use aoc::args::Args;
+use aoc::input::LineReader;
+use aoc::parse::{self, ParseError};
+use aoc::rng::Rng;
+
+mod diff;
+mod gen;
 
 #[derive(Clone)]
 struct PathWalker {
@@ -34,28 +39,27 @@ struct Map {
 }
 
 impl Map {
-    fn new(data: &str) -> Map {
+    /* One "AAA = (BBB, CCC)" line per node, the first one
+    being line number first of the input */
+    fn new(lines: &[String], first: usize) -> Result<Map, ParseError> {
         let mut edges: HashMap<String, (String, String)> = HashMap::new();
 
-        for l in data.split('\n') {
-            let sp: Vec<&str> = l.split('=').collect();
-            assert!(sp.len() == 2);
-
-            let name = sp[0].trim().to_string();
-
-            let clean_str = sp[1].replace(['(', ')'], "");
-            let choices: Vec<&str> = clean_str.split(',').collect();
-            assert!(choices.len() == 2);
-
-            let left = choices[0].trim().to_string();
-            let right = choices[1].trim().to_string();
+        for (x, l) in lines.iter().enumerate() {
+            let line = first + x;
+            let (name, choices) = parse::keyed_tuple(l).map_err(|e| e.at_line(line))?;
 
-            edges.insert(name, (left, right));
+            if let [left, right] = choices[..] {
+                edges.insert(name.to_string(), (left.to_string(), right.to_string()));
+            } else {
+                return Err(ParseError {
+                    line: Some(line),
+                    col: 1,
+                    msg: format!("{} needs 2 choices, got {}", name, choices.len()),
+                });
+            }
         }
 
-        println!("{:?}", edges);
-
-        Map { edges }
+        Ok(Map { edges })
     }
 
     fn goto(&self, dest: &str) -> Option<&(String, String)> {
@@ -81,27 +85,132 @@ impl Map {
             .cloned()
             .collect()
     }
+
+    /* Steps from start to its first Z node, None if it never gets
+    there: after nodes * path steps the walk can only be looping */
+    fn steps_to_z(&self, path: &PathWalker, start: &str) -> Option<u64> {
+        let limit = (self.edges.len() * path.path.len()) as u64;
+        let mut cur = start.to_string();
+
+        for (cnt, v) in path.clone().enumerate().take(limit as usize + 1) {
+            cur = self.next(&cur, &v);
+
+            if cur.ends_with('Z') {
+                return Some(cnt as u64 + 1);
+            }
+        }
+
+        None
+    }
+
+    /* Part 2 assuming each ghost loops over its first Z node
+    with a period of the steps it took to get there */
+    fn ghosts_lcm(&self, path: &PathWalker) -> Option<u64> {
+        let iter_vals: Option<Vec<u64>> = self
+            .start_nodes()
+            .iter()
+            .map(|c| self.steps_to_z(path, c))
+            .collect();
+
+        aoc::math::lcm_all(&iter_vals?)
+    }
+
+    /* Part 2 walking all the ghosts together, giving up after limit
+    steps. Real inputs take trillions, only --diff uses it */
+    fn ghosts_walk(&self, path: &PathWalker, limit: u64) -> Option<u64> {
+        let mut path = path.clone();
+        let mut currents = self.start_nodes();
+
+        for cnt in 1..=limit {
+            let choice = path.next().unwrap();
+            currents = currents.iter().map(|v| self.next(v, &choice)).collect();
+
+            if currents.iter().all(|v| v.ends_with('Z')) {
+                return Some(cnt);
+            }
+        }
+
+        None
+    }
 }
 
-fn main() {
-    let f = File::open("data.txt").unwrap();
-    let mut r = BufReader::new(f);
+/* Instructions line, blank line, then the nodes */
+fn read<R: BufRead>(r: LineReader<R>) -> Result<(PathWalker, Map), ParseError> {
+    let mut lines: Vec<String> = r.lines().map(|l| l.unwrap()).collect();
 
-    let mut data = String::new();
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
 
-    r.read_to_string(&mut data).unwrap();
+    let fail = |line: usize, msg: &str| ParseError {
+        line: Some(line),
+        col: 1,
+        msg: msg.to_string(),
+    };
 
-    let data_split: Vec<&str> = data.split("\n\n").collect();
-    assert!(data_split.len() == 2);
+    if lines.first().is_none_or(|l| l.trim().is_empty()) {
+        return Err(fail(1, "expected the instructions"));
+    }
+    if lines.get(1).is_some_and(|l| !l.trim().is_empty()) {
+        return Err(fail(2, "expected a blank line"));
+    }
+    if lines.len() < 3 {
+        return Err(fail(3, "expected the nodes"));
+    }
+
+    Ok((PathWalker::new(&lines[0]), Map::new(&lines[2..], 3)?))
+}
 
-    let path = PathWalker::new(data_split[0]);
-    let map = Map::new(data_split[1]);
+/* Same from a string, for the generated inputs */
+fn load(data: &str) -> Result<(PathWalker, Map), ParseError> {
+    read(LineReader::new(data.as_bytes()))
+}
+
+fn main() {
+    /* d8 --gen SEED [GHOSTS] [PATH] [MULT] [SKEW] prints a random network */
+    if let Some(a) = Args::get("--gen") {
+        let mut rng = Rng::new(a.need(0, "a seed"));
+        print!(
+            "{}",
+            gen::network(
+                &mut rng,
+                a.num(1, 4),
+                a.num(2, 20),
+                a.num(3, 10),
+                a.num(4, 0)
+            )
+        );
+        return;
+    }
+
+    /* d8 --diff [RUNS] [GHOSTS] [PATH] [MULT] [SKEW] checks the LCM
+    shortcut against walking the ghosts together */
+    if let Some(a) = Args::get("--diff") {
+        let d = diff::Ghosts {
+            ghosts: a.num(1, 3),
+            path: a.num(2, 5),
+            mult: a.num(3, 5),
+            skew: a.num(4, 0),
+        };
+        aoc::diff::run(&d, a.num(0, 100));
+        return;
+    }
+
+    let (path, map) = match read(aoc::input::open("data.txt").unwrap()) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Bad map at {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{:?}", map.edges);
 
     let mut cur = map.goto("AAA").unwrap();
 
     let mut cnt = 0;
 
-    for v in path.into_iter() {
+    for v in path.clone() {
         let next = match v {
             b'L' => cur.0.as_str(),
             b'R' => cur.1.as_str(),
@@ -122,85 +231,55 @@ fn main() {
         }
     }
 
-    println!("Part 1 iter values is {}", cnt);
+    aoc::ledger::answer(1, cnt);
 
     // Part 2 Get Loop Length USING LCM
-    let currents = map.start_nodes();
-    let mut path = PathWalker::new(data_split[0]);
-
-    let mut iter_vals: Vec<u64> = Vec::new();
-
-    for c in currents.iter() {
-        let mut cur = map.goto(c).unwrap();
-
-        let mut cnt = 0;
-
-        for v in path.clone().into_iter() {
-            let next = match v {
-                b'L' => cur.0.as_str(),
-                b'R' => cur.1.as_str(),
-                _ => unreachable!("No such way {}", v),
-            };
-
-            let candi = map.goto(next);
-            cnt += 1;
-
-            if next.ends_with('Z') {
-                break;
-            }
-
-            if let Some(candi) = candi {
-                cur = candi;
-            } else {
-                unreachable!("We are at a dead end ({}, {})", cur.0, cur.1);
-            }
-        }
-
-        iter_vals.push(cnt);
+    for c in map.start_nodes().iter() {
+        let cnt = map.steps_to_z(&path, c).expect("ghost never reaches Z");
         println!("{} iter values is {}", c, cnt);
     }
 
-    let lcm = iter_vals.iter().fold(1, |a, b| num::integer::lcm(a, *b));
-
-    println!("LCM {}", lcm);
-
-    // Part 2 (Brute FOOORRRCE)
-
-    let mut path = PathWalker::new(data_split[0]);
-
-    let currents = map.start_nodes();
-
-    let mut currents: Vec<String> = currents.iter().map(|v| v.to_string()).collect();
-
-    let mut cnt: u64 = 0;
-    let mut last_cnt = 0;
-
-    let bar = ProgressBar::new(lcm);
-    bar.set_style(
-        ProgressStyle::with_template(
-            "[{elapsed_precise} / {eta_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
-        )
-        .unwrap()
-        .progress_chars("##-"),
-    );
+    let lcm = map.ghosts_lcm(&path).expect("LCM does not fit in 64 bits");
 
-    loop {
-        let choice = path.next().unwrap();
-        currents = currents.iter().map(|v| map.next(v, &choice)).collect();
-
-        let count_with_z = currents.iter().filter(|v| v.ends_with('Z')).count();
+    aoc::ledger::answer(2, lcm);
+}
 
-        cnt += 1;
-        bar.inc(1);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if count_with_z > 0 && (cnt - last_cnt) > 100000 {
-            last_cnt = cnt;
-        }
+    fn error(data: &str) -> String {
+        load(data).err().unwrap().to_string()
+    }
 
-        if count_with_z == currents.len() {
-            break;
-        }
+    #[test]
+    fn example() {
+        let (path, map) =
+            load("LLR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        assert_eq!(path.path, b"LLR");
+        assert_eq!(map.edges.len(), 3);
     }
 
-    println!("Part 2 iter values is {}", cnt);
+    #[test]
+    fn error_lines() {
+        assert_eq!(error(""), "line 1 col 1: expected the instructions");
+        assert_eq!(error("LR\n"), "line 3 col 1: expected the nodes");
+        assert_eq!(error("LR\n\n\n"), "line 3 col 1: expected the nodes");
+        assert_eq!(
+            error("LR\nAAA = (B, C)\n"),
+            "line 2 col 1: expected a blank line"
+        );
+        assert_eq!(
+            error("LR\nx\nAAA = (B, C)\n"),
+            "line 2 col 1: expected a blank line"
+        );
+        assert_eq!(
+            error("LR\n\nAAA = (B, C)\nBBB = (C)\n"),
+            "line 4 col 1: BBB needs 2 choices, got 1"
+        );
+        assert_eq!(
+            error("LR\n\nAAA = (B, C)\nBBB = (C, D)\nCCC (D, D)\n"),
+            "line 5 col 5: expected '='"
+        );
+    }
 }
@@ -1,33 +1,35 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use aoc::parse::{self, ParseError};
 
+/* Running sums of the predictions, each line is
+folded in as it is read and not kept */
 struct Scan {
-    array: Vec<Vec<i64>>,
+    forwards: i64,
+    backwards: i64,
 }
 
 impl Scan {
     fn new() -> Scan {
-        Scan { array: Vec::new() }
+        Scan {
+            forwards: 0,
+            backwards: 0,
+        }
     }
 
-    fn push(&mut self, data: &str) {
-        let mut numbers: Vec<i64> = Vec::new();
+    /* Blank lines have nothing to predict and are skipped */
+    fn push(&mut self, data: &str) -> Result<(), ParseError> {
+        let line = parse::numbers(data)?;
 
-        for v in data.split(' ') {
-            let n = v.trim().parse::<i64>().unwrap();
-            numbers.push(n);
+        if line.is_empty() {
+            return Ok(());
         }
 
-        self.array.push(numbers);
+        self.forwards += Scan::predict(&line, false);
+        self.backwards += Scan::predict(&line, true);
+        Ok(())
     }
 
     fn fold(line: &[i64]) -> Vec<i64> {
-        let mut ret: Vec<i64> = Vec::new();
-        for i in 0..line.len() - 1 {
-            ret.push(line[i + 1] - line[i]);
-        }
-
-        ret
+        line.windows(2).map(|v| v[1] - v[0]).collect()
     }
 
     fn is_zeroes(line: &[i64]) -> bool {
@@ -57,37 +59,57 @@ impl Scan {
 
         /* We now have unfolded the list */
 
-        let pred = preds.iter().map(|v| v.last().unwrap()).sum();
+        let pred = preds.iter().filter_map(|v| v.last()).sum();
         println!("{:?} == {}", preds, pred);
         pred
     }
 
     fn sum_of_preds(&self, backwards: bool) -> i64 {
-        self.array.iter().map(|v| Scan::predict(v, backwards)).sum()
+        if backwards {
+            self.backwards
+        } else {
+            self.forwards
+        }
     }
 }
 
 fn main() {
-    let f = File::open("data.txt").unwrap();
-    let mut r = BufReader::new(f);
+    let mut sc = Scan::new();
 
-    let mut data = String::new();
+    for (x, line) in aoc::input::open("data.txt").unwrap().lines().enumerate() {
+        sc.push(&line.unwrap())
+            .map_err(|e| e.at_line(x + 1))
+            .unwrap();
+    }
 
-    let mut sc = Scan::new();
+    aoc::ledger::answer(1, sc.sum_of_preds(false));
+    aoc::ledger::answer(2, sc.sum_of_preds(true));
+}
 
-    while let Ok(l) = r.read_line(&mut data) {
-        if l == 0 {
-            break;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        sc.push(data.as_str());
+    fn scan(lines: &[&str]) -> (i64, i64) {
+        let mut sc = Scan::new();
+        for l in lines {
+            sc.push(l).unwrap();
+        }
+        (sc.sum_of_preds(false), sc.sum_of_preds(true))
+    }
 
-        data.clear();
+    #[test]
+    fn example() {
+        assert_eq!(
+            scan(&["0 3 6 9 12 15", "1 3 6 10 15 21", "10 13 16 21 30 45"]),
+            (114, 2)
+        );
     }
 
-    println!(
-        "Part 1 : {} && Part 2 : {}",
-        sc.sum_of_preds(false),
-        sc.sum_of_preds(true)
-    );
+    #[test]
+    fn short_lines() {
+        assert_eq!(scan(&["", "  ", "0 3 6 9 12 15", ""]), (18, -3));
+        assert_eq!(scan(&["7"]), (7, 7));
+        assert_eq!(scan(&["-2 4"]), (10, -8));
+    }
 }